@@ -0,0 +1,24 @@
+/// plain rgba colour so the core doesn't need raylib
+/// values match the raylib palette so the front-end can convert 1:1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub const GRAY: Color = Color::new(130, 130, 130, 255);
+    pub const YELLOW: Color = Color::new(253, 249, 0, 255);
+    pub const BLUE: Color = Color::new(0, 121, 241, 255);
+    pub const BROWN: Color = Color::new(127, 106, 79, 255);
+    pub const SKYBLUE: Color = Color::new(102, 191, 255, 255);
+    pub const PURPLE: Color = Color::new(200, 122, 255, 255);
+    pub const GREEN: Color = Color::new(0, 228, 48, 255);
+    pub const RED: Color = Color::new(230, 41, 55, 255);
+}
@@ -0,0 +1,1636 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::mode::{ticks_to_duration, Finish};
+use crate::replay::{Replay, Summary};
+use crate::scoring::{Clear, Scoring, Spin, HARD_DROP_POINTS, SOFT_DROP_POINTS};
+use crate::{
+    AnyRandomizer, InputFrame, Randomizer, Rotation, Settings, Tetromino, GRAVITY_ONE,
+    MAX_NEXT_QUEUE,
+};
+
+// cheat and copy the rosetta code go implementation
+pub const GRID_HORIZONTAL_SIZE: usize = 12;
+pub const GRID_VERTICAL_SIZE: usize = 20;
+const FADING_TIME: u16 = 33;
+/// how many times a second `Game::step` is expected to be called,
+/// see `FixedTimestep` for running it at this rate whatever the frame rate
+pub const TICKS_PER_SECOND: u32 = 60;
+// how long a t-spin or tetris stays up on screen
+const CALLOUT_TIME: u32 = 2 * TICKS_PER_SECOND;
+// the last of a quarter turn's kicks, the big (1, 2) one a T only takes
+// to get into a proper t-spin slot
+const T_SPIN_KICK: usize = 4;
+
+// these maybe should have associated values or smth
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridSquare {
+    Empty, // nothing in the grid square
+    Full,  // square is full, no longer in flight
+    Block, // dunno
+    Fading,
+}
+
+// next defines bunch of variables
+// should be in a mutable struct
+#[derive(Serialize, Deserialize)]
+pub struct Game {
+    // set once the game's over, however that happened
+    finish: Option<Finish>,
+    // ticks played so far, for the timed modes
+    ticks: u32,
+    // every input stepped with, as `InputFrame::to_bits`, for replays
+    inputs: Vec<u8>,
+
+    // These variables keep track of the active piece position
+    // (top left of its 4x4 box, so can go off the edge of the grid)
+    piece_position_x: i32,
+    piece_position_y: i32,
+    piece_rotation: Rotation,
+
+    // Statistics
+    level: u16,
+    lines: u16,
+    score: u32,
+    // pieces locked into the grid
+    pieces: u32,
+    scoring: Scoring,
+    // the last clear worth shouting about, and ticks left to show it for
+    callout: Option<Clear>,
+    callout_counter: u32,
+
+    // Based on level, in GRAVITY_ONEths of a row per tick
+    gravity_speed: u32,
+
+    // grid
+    grid: [[GridSquare; GRID_VERTICAL_SIZE]; GRID_HORIZONTAL_SIZE],
+    piece: Tetromino,
+    // upcoming pieces, front comes out next
+    next_queue: VecDeque<Tetromino>,
+    randomizer: AnyRandomizer,
+    settings: Settings,
+
+    // held piece, and whether it's been swapped since the last piece locked
+    hold: Option<Tetromino>,
+    hold_used: bool,
+    seed: u64,
+
+    // game parameters
+    piece_active: bool,
+    detection: bool,
+    line_to_delete: bool,
+
+    // buttons held on the previous step, used to work out presses
+    last_input: InputFrame,
+
+    // counters
+    gravity_movement_counter: u32,
+    fade_line_counter: u16,
+
+    // direction the player's holding, ticks it's been held for (up to the
+    // auto shift delay) and ticks since the last auto repeat
+    das_direction: i32,
+    das_counter: u32,
+    arr_counter: u32,
+
+    // ticks the piece has been sat on something, how many times moving
+    // has put that back to zero, and the lowest row the piece has reached
+    lock_delay_counter: u32,
+    lock_resets: u16,
+    lowest_position_y: i32,
+
+    // the last thing the piece did if it was turn, for spotting t-spins
+    last_turn: Option<Turn>,
+}
+
+/// a turn the piece in flight made
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Turn {
+    // a 180 rather than a quarter turn
+    half: bool,
+    // which of the kicks it took, from 0 for none
+    kick: usize,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::with_settings(Settings::default())
+    }
+
+    pub fn with_settings(settings: Settings) -> Self {
+        Self::with_seed(settings, thread_rng().gen())
+    }
+
+    /// a new game that plays out exactly the same every time
+    /// for the same settings, seed and inputs
+    pub fn with_seed(mut settings: Settings, seed: u64) -> Self {
+        settings.next_queue = settings.next_queue.clamp(1, MAX_NEXT_QUEUE);
+
+        let mut randomizer = settings.randomizer.create(seed);
+        let next_queue: VecDeque<_> = (0..settings.next_queue)
+            .map(|_| randomizer.next())
+            .collect();
+
+        let mut grid = [[GridSquare::Empty; GRID_VERTICAL_SIZE]; GRID_HORIZONTAL_SIZE];
+        for (i, column) in grid.iter_mut().enumerate() {
+            for (j, square) in column.iter_mut().enumerate() {
+                if (j == GRID_VERTICAL_SIZE - 1) || (i == 0) || (i == GRID_HORIZONTAL_SIZE - 1) {
+                    *square = GridSquare::Block;
+                }
+            }
+        }
+
+        Game {
+            finish: None,
+            ticks: 0,
+            inputs: Vec::new(),
+
+            // These variables keep track of the active piece position
+            piece_position_x: 0,
+            piece_position_y: 0,
+            piece_rotation: Rotation::Spawn,
+
+            // Statistics
+            level: 1,
+            lines: 0,
+            score: 0,
+            pieces: 0,
+            scoring: Scoring::new(),
+            callout: None,
+            callout_counter: 0,
+
+            // Based on level
+            gravity_speed: settings.gravity_curve.gravity(1),
+            grid,
+            // replaced by the incoming piece before it's ever used
+            piece: next_queue[0],
+            next_queue,
+            randomizer,
+            settings,
+            hold: None,
+            hold_used: false,
+            seed,
+            piece_active: false,
+            detection: false,
+            line_to_delete: false,
+            last_input: InputFrame::default(),
+            gravity_movement_counter: 0,
+            fade_line_counter: 0,
+            das_direction: 0,
+            das_counter: 0,
+            arr_counter: 0,
+            lock_delay_counter: 0,
+            lock_resets: 0,
+            lowest_position_y: 0,
+            last_turn: None,
+        }
+    }
+
+    /// advance the game by a single tick
+    pub fn step(&mut self, input: InputFrame) {
+        let pressed = input.pressed_since(self.last_input);
+        self.last_input = input;
+
+        // do nothing if the game is over
+        if self.finish.is_some() {
+            return;
+        }
+        self.inputs.push(input.to_bits());
+        self.ticks += 1;
+        if self
+            .settings
+            .mode
+            .time_limit()
+            .is_some_and(|limit| self.ticks >= limit)
+        {
+            self.finish = Some(Finish::TimeUp);
+            return;
+        }
+        if self.callout_counter > 0 {
+            self.callout_counter -= 1;
+        }
+
+        if !self.line_to_delete {
+            if !self.piece_active {
+                // get another piece
+                self.piece_active = self.create_piece();
+            } else if pressed.hard_drop {
+                self.hard_drop();
+            } else if pressed.hold && !self.hold_used {
+                // swap the piece in flight for the held one
+                self.hold_piece();
+            } else {
+                // move laterally at player's will, with auto repeat
+                self.resolve_lateral_movement(input, pressed);
+
+                // turn the piece at the players will
+                self.resolve_turn_movement(pressed);
+
+                // fall down, faster while soft dropping
+                self.gravity_movement_counter += if input.soft_drop {
+                    self.gravity_speed
+                        .saturating_mul(u32::from(self.settings.soft_drop_factor))
+                } else {
+                    self.gravity_speed
+                };
+                while self.gravity_movement_counter >= GRAVITY_ONE {
+                    self.gravity_movement_counter -= GRAVITY_ONE;
+
+                    // basic falling movement
+                    self.check_detection();
+                    if self.detection {
+                        self.gravity_movement_counter = 0;
+                    } else if input.soft_drop {
+                        self.score = self.score.saturating_add(SOFT_DROP_POINTS);
+                    }
+
+                    // check if piece has collided with another piece
+                    // or with the boundaries
+                    self.resolve_falling_movement();
+                }
+
+                // lock the piece if it's been sat on something long enough
+                self.resolve_lock_delay();
+            }
+
+            // game over logic
+            for j in 0..2 {
+                for i in 1..GRID_HORIZONTAL_SIZE {
+                    if self.grid[i][j] == GridSquare::Full {
+                        // unless that last piece just won it
+                        self.finish.get_or_insert(Finish::ToppedOut);
+                    }
+                }
+            }
+        } else {
+            // animation when deleting lines
+            self.fade_line_counter += 1;
+
+            if self.fade_line_counter >= FADING_TIME {
+                self.delete_complete_lines();
+                self.fade_line_counter = 0;
+                self.line_to_delete = false;
+            }
+        }
+    }
+
+    pub fn grid(&self) -> &[[GridSquare; GRID_VERTICAL_SIZE]; GRID_HORIZONTAL_SIZE] {
+        &self.grid
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// seed the randomizer started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// the piece in flight
+    pub fn piece(&self) -> Tetromino {
+        self.piece
+    }
+
+    /// grid coordinates of the squares of the piece in flight, if there is one
+    pub fn piece_cells(&self) -> Option<[(i32, i32); 4]> {
+        if self.piece_active {
+            Some(self.cells_at(
+                self.piece_position_x,
+                self.piece_position_y,
+                self.piece_rotation,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// grid coordinates of where the piece in flight would land if hard dropped
+    pub fn ghost_cells(&self) -> Option<[(i32, i32); 4]> {
+        if self.piece_active {
+            Some(self.cells_at(
+                self.piece_position_x,
+                self.piece_position_y + self.drop_distance(),
+                self.piece_rotation,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// the piece in the hold slot
+    pub fn hold(&self) -> Option<Tetromino> {
+        self.hold
+    }
+
+    /// true if the piece in flight has already been swapped with the hold slot
+    pub fn hold_used(&self) -> bool {
+        self.hold_used
+    }
+
+    /// upcoming pieces, in the order they'll come out
+    pub fn next_queue(&self) -> impl Iterator<Item = Tetromino> + '_ {
+        self.next_queue.iter().copied()
+    }
+
+    pub fn level(&self) -> u16 {
+        self.level
+    }
+
+    pub fn lines(&self) -> u16 {
+        self.lines
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// pieces locked into the grid so far
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+
+    /// the last t-spin, tetris or perfect clear, for a little while after
+    pub fn callout(&self) -> Option<Clear> {
+        self.callout.filter(|_| self.callout_counter > 0)
+    }
+
+    /// combo and back to back state
+    pub fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.finish.is_some()
+    }
+
+    /// everything needed to play this game back, up to now
+    pub fn replay(&self) -> Replay {
+        Replay {
+            settings: self.settings,
+            seed: self.seed,
+            inputs: self.inputs.clone(),
+            claimed: Some(Summary::of(self)),
+        }
+    }
+
+    /// how the game ended, if it has
+    pub fn finish(&self) -> Option<Finish> {
+        self.finish
+    }
+
+    /// ticks played, stopping when the game's over
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// a fingerprint of what's in the grid, the same on every machine and build
+    pub fn board_hash(&self) -> u64 {
+        // fnv-1a, std's hasher is allowed to change between releases
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for square in self.grid.iter().flatten() {
+            hash ^= *square as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// time played, stopping when the game's over
+    pub fn elapsed(&self) -> Duration {
+        ticks_to_duration(self.ticks)
+    }
+
+    /// time left on the clock for a timed mode
+    pub fn time_left(&self) -> Option<Duration> {
+        let limit = self.settings.mode.time_limit()?;
+        Some(ticks_to_duration(limit.saturating_sub(self.ticks)))
+    }
+
+    /// what's wrong with a game that's been loaded from outside, if anything,
+    /// so it can't panic partway through a step. every counter has to be
+    /// somewhere a step could have left it, the running totals that can't be
+    /// bounded like that (score and lines) only ever saturate
+    pub(crate) fn check(&self) -> Result<(), &'static str> {
+        self.settings.check()?;
+        // one input's kept for every tick
+        if self.inputs.len() != self.ticks as usize {
+            return Err("ticks");
+        }
+        if self.pieces > self.ticks {
+            return Err("pieces");
+        }
+        let level = match self.settings.mode.levels_up() {
+            true => 1 + self.lines / self.settings.lines_per_level.max(1),
+            false => 1,
+        };
+        if self.level != level {
+            return Err("level");
+        }
+        // a combo's one less than the clears in it, and each clear's a line at least
+        if self
+            .scoring
+            .combo()
+            .is_some_and(|combo| combo >= u32::from(self.lines))
+        {
+            return Err("combo");
+        }
+        if self.fade_line_counter >= FADING_TIME {
+            return Err("fade line counter");
+        }
+        if self.callout_counter > CALLOUT_TIME {
+            return Err("callout counter");
+        }
+        if self.gravity_movement_counter >= GRAVITY_ONE {
+            return Err("gravity movement counter");
+        }
+        if self.das_counter > ms_to_ticks(self.settings.das_ms)
+            || self.arr_counter > ms_to_ticks(self.settings.arr_ms)
+        {
+            return Err("auto repeat");
+        }
+        if self.lock_delay_counter > ms_to_ticks(self.settings.lock_delay_ms)
+            || self.lock_resets > self.settings.lock_resets
+        {
+            return Err("lock delay");
+        }
+        if self.next_queue.len() != self.settings.next_queue {
+            return Err("next queue");
+        }
+        if self.piece_active
+            && !self.piece_fits(
+                self.piece_position_x,
+                self.piece_position_y,
+                self.piece_rotation,
+            )
+        {
+            return Err("piece position");
+        }
+        if self.gravity_speed != self.settings.gravity_curve.gravity(self.level) {
+            return Err("gravity");
+        }
+        Ok(())
+    }
+
+    /// ticks spent so far in the line clear animation
+    pub fn fade_line_counter(&self) -> u16 {
+        self.fade_line_counter
+    }
+
+    /// move the next piece into play & top up the queue
+    fn create_piece(&mut self) -> bool {
+        // never empty, always topped back up straight away
+        let piece = self.next_queue.pop_front().unwrap();
+        self.next_queue.push_back(self.randomizer.next());
+        self.spawn(piece);
+
+        // new piece, so it can be held again
+        self.hold_used = false;
+
+        // todo: no point to this return
+        true
+    }
+
+    /// start a piece falling from the top
+    fn spawn(&mut self, piece: Tetromino) {
+        // new piece position at centre of top of board
+        self.piece_position_x = (GRID_HORIZONTAL_SIZE as i32 - 4) / 2; // todo: magic numbers
+        self.piece_position_y = 0;
+        self.piece_rotation = Rotation::Spawn;
+        self.piece = piece;
+
+        self.gravity_movement_counter = 0;
+        self.detection = false;
+        self.lock_delay_counter = 0;
+        self.lock_resets = 0;
+        self.lowest_position_y = self.piece_position_y;
+        self.last_turn = None;
+    }
+
+    /// put the piece in flight in the hold slot, and bring out whatever was
+    /// there before, or the next piece if it was empty
+    /// only allowed once per piece
+    fn hold_piece(&mut self) {
+        match self.hold.replace(self.piece) {
+            Some(held) => self.spawn(held),
+            None => {
+                self.create_piece();
+            }
+        }
+        self.hold_used = true;
+    }
+
+    /// grid coordinates of the active piece's squares if it were at x, y with rotation
+    fn cells_at(&self, x: i32, y: i32, rotation: Rotation) -> [(i32, i32); 4] {
+        self.piece
+            .cells(rotation)
+            .map(|(cell_x, cell_y)| (x + cell_x, y + cell_y))
+    }
+
+    /// true if the active piece could sit at x, y with rotation without overlapping anything
+    fn piece_fits(&self, x: i32, y: i32, rotation: Rotation) -> bool {
+        self.cells_at(x, y, rotation).iter().all(|&(i, j)| {
+            i >= 0
+                && j >= 0
+                && (i as usize) < GRID_HORIZONTAL_SIZE
+                && (j as usize) < GRID_VERTICAL_SIZE
+                && self.grid[i as usize][j as usize] == GridSquare::Empty
+        })
+    }
+
+    fn resolve_falling_movement(&mut self) {
+        // if the piece has landed it stays put until the lock delay runs out
+        if !self.detection {
+            // we move down the piece
+            self.piece_position_y += 1;
+            self.last_turn = None;
+
+            // getting lower than ever before earns back all the lock delay resets
+            if self.piece_position_y > self.lowest_position_y {
+                self.lowest_position_y = self.piece_position_y;
+                self.lock_resets = 0;
+            }
+        }
+    }
+
+    fn resolve_lock_delay(&mut self) {
+        self.check_detection();
+        if !self.detection {
+            self.lock_delay_counter = 0;
+            return;
+        }
+
+        self.lock_delay_counter += 1;
+        if self.lock_delay_counter >= ms_to_ticks(self.settings.lock_delay_ms)
+            || self.lock_resets >= self.settings.lock_resets
+        {
+            let spin = self.lock_piece();
+
+            // check if we completed a line and if so erase the line
+            // and pull down lines above
+            self.check_completion(spin);
+        }
+    }
+
+    /// the piece moved or turned, so give it more time before it locks
+    /// (only so many times though, or it could be spun forever)
+    fn reset_lock_delay(&mut self) {
+        if self.lock_delay_counter > 0 {
+            self.lock_delay_counter = 0;
+            self.lock_resets += 1;
+        }
+    }
+
+    /// stamp the piece in flight into the grid, it's done moving
+    /// returns whether it was spun in
+    fn lock_piece(&mut self) -> Spin {
+        let spin = self.spin();
+        for (i, j) in self.cells_at(
+            self.piece_position_x,
+            self.piece_position_y,
+            self.piece_rotation,
+        ) {
+            self.grid[i as usize][j as usize] = GridSquare::Full;
+        }
+        self.detection = false;
+        self.piece_active = false;
+        self.pieces += 1;
+        spin
+    }
+
+    /// whether the piece in flight is a T that's just been turned into a
+    /// spot with at least three of the corners around its centre filled
+    /// it's a full t-spin if both corners it points at are filled, or a
+    /// quarter turn took the big (1, 2) kick to get there, otherwise only a mini
+    fn spin(&self) -> Spin {
+        let turn = match self.last_turn {
+            Some(turn) if self.piece == Tetromino::T => turn,
+            _ => return Spin::None,
+        };
+
+        // corners of the 3x3 box the T turns in, outside the grid counts as filled
+        let filled = |(dx, dy): (i32, i32)| {
+            let i = self.piece_position_x + dx;
+            let j = self.piece_position_y + dy;
+            !(0..GRID_HORIZONTAL_SIZE as i32).contains(&i)
+                || !(0..GRID_VERTICAL_SIZE as i32).contains(&j)
+                || self.grid[i as usize][j as usize] != GridSquare::Empty
+        };
+        let corners = [(0, 0), (2, 0), (2, 2), (0, 2)];
+        if corners.iter().filter(|&&corner| filled(corner)).count() < 3 {
+            return Spin::None;
+        }
+
+        // the two corners either side of the way the T points
+        let front = match self.piece_rotation {
+            Rotation::Spawn => [corners[0], corners[1]],
+            Rotation::Right => [corners[1], corners[2]],
+            Rotation::Reverse => [corners[2], corners[3]],
+            Rotation::Left => [corners[3], corners[0]],
+        };
+        if front.into_iter().all(filled) || (!turn.half && turn.kick == T_SPIN_KICK) {
+            Spin::Full
+        } else {
+            Spin::Mini
+        }
+    }
+
+    /// how many rows the piece in flight can fall before it lands
+    fn drop_distance(&self) -> i32 {
+        let mut distance = 0;
+        while self.piece_fits(
+            self.piece_position_x,
+            self.piece_position_y + distance + 1,
+            self.piece_rotation,
+        ) {
+            distance += 1;
+        }
+        distance
+    }
+
+    /// send the piece in flight straight to the bottom and lock it there
+    fn hard_drop(&mut self) {
+        let distance = self.drop_distance();
+        self.piece_position_y += distance;
+        self.score = self
+            .score
+            .saturating_add(HARD_DROP_POINTS * distance as u32);
+        if distance > 0 {
+            self.last_turn = None;
+        }
+        let spin = self.lock_piece();
+        self.check_completion(spin);
+    }
+
+    fn resolve_lateral_movement(&mut self, input: InputFrame, pressed: InputFrame) {
+        // which way we want to go, if both are held the last one pressed wins
+        let direction = match (input.left, input.right) {
+            (true, false) => -1,
+            (false, true) => 1,
+            (true, true) if pressed.left => -1,
+            (true, true) if pressed.right => 1,
+            (true, true) if self.das_direction != 0 => self.das_direction,
+            (true, true) => -1,
+            (false, false) => 0,
+        };
+
+        // new direction, move straight away and start charging
+        if direction != self.das_direction {
+            self.das_direction = direction;
+            self.das_counter = 0;
+            self.arr_counter = 0;
+            if direction != 0 {
+                self.shift(direction);
+            }
+            return;
+        }
+        if direction == 0 {
+            return;
+        }
+
+        // held long enough, start auto repeating
+        let das_ticks = ms_to_ticks(self.settings.das_ms);
+        let arr_ticks = ms_to_ticks(self.settings.arr_ms);
+        if self.das_counter < das_ticks {
+            self.das_counter += 1;
+            if self.das_counter < das_ticks {
+                return;
+            }
+        } else {
+            self.arr_counter += 1;
+            if self.arr_counter < arr_ticks {
+                return;
+            }
+        }
+        self.arr_counter = 0;
+
+        if arr_ticks == 0 {
+            // no repeat rate at all means straight to the wall
+            while self.shift(direction) {}
+        } else {
+            self.shift(direction);
+        }
+    }
+
+    /// move the piece in flight one column, false if something's in the way
+    fn shift(&mut self, direction: i32) -> bool {
+        let x = self.piece_position_x + direction;
+        if self.piece_fits(x, self.piece_position_y, self.piece_rotation) {
+            self.piece_position_x = x;
+            self.last_turn = None;
+            self.reset_lock_delay();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn resolve_turn_movement(&mut self, pressed: InputFrame) {
+        // input for turning the piece
+        let rotation = if pressed.rotate_cw {
+            self.piece_rotation.cw()
+        } else if pressed.rotate_ccw {
+            self.piece_rotation.ccw()
+        } else if pressed.rotate_180 {
+            self.piece_rotation.flip()
+        } else {
+            return;
+        };
+
+        self.try_rotate(rotation);
+    }
+
+    /// turn the active piece to rotation, trying each wall kick in turn
+    /// returns false if none of them fit and the piece stayed put
+    fn try_rotate(&mut self, rotation: Rotation) -> bool {
+        let half = rotation == self.piece_rotation.flip();
+        let kicks = self.piece.kicks(self.piece_rotation, rotation);
+        for (kick, &(dx, dy)) in kicks.iter().enumerate() {
+            let x = self.piece_position_x + dx;
+            let y = self.piece_position_y + dy;
+            if self.piece_fits(x, y, rotation) {
+                self.piece_position_x = x;
+                self.piece_position_y = y;
+                self.piece_rotation = rotation;
+                self.last_turn = Some(Turn { half, kick });
+                self.reset_lock_delay();
+                return true;
+            }
+        }
+        false
+    }
+
+    // collisions
+    fn check_detection(&mut self) {
+        self.detection = !self.piece_fits(
+            self.piece_position_x,
+            self.piece_position_y + 1,
+            self.piece_rotation,
+        );
+    }
+
+    fn check_completion(&mut self, spin: Spin) {
+        let mut cleared = 0;
+        for j in (0..=GRID_VERTICAL_SIZE - 2).rev() {
+            let mut calculator = 0;
+            for i in 1..GRID_HORIZONTAL_SIZE - 1 {
+                // count each square of the line
+                if self.grid[i][j] == GridSquare::Full {
+                    calculator += 1;
+                }
+
+                // check if we completed the whole line
+                if calculator == GRID_HORIZONTAL_SIZE - 2 {
+                    self.line_to_delete = true;
+                    calculator = 0;
+                    cleared += 1;
+
+                    // mark the completed line
+                    for z in 1..GRID_HORIZONTAL_SIZE - 1 {
+                        self.grid[z][j] = GridSquare::Fading;
+                    }
+                }
+            }
+        }
+
+        // everything left is about to fade away
+        let perfect = cleared > 0
+            && self
+                .grid
+                .iter()
+                .all(|column| !column.contains(&GridSquare::Full));
+        let clear = Clear {
+            lines: cleared,
+            perfect,
+            spin,
+        };
+        self.score = self
+            .score
+            .saturating_add(self.scoring.lock(clear, self.level));
+        self.add_lines(cleared);
+        if perfect || clear.name().is_some() {
+            self.callout = Some(clear);
+            self.callout_counter = CALLOUT_TIME;
+        }
+    }
+
+    fn delete_complete_lines(&mut self) {
+        for j in (0..=GRID_VERTICAL_SIZE - 2).rev() {
+            // stay on this row until whatever falls into it isn't cleared too
+            while self.grid[1][j] == GridSquare::Fading {
+                // pull every row above down by one, overwriting the cleared line
+                for column in self.grid[1..GRID_HORIZONTAL_SIZE - 1].iter_mut() {
+                    column.copy_within(0..j, 1);
+                    column[0] = GridSquare::Empty;
+                }
+            }
+        }
+    }
+
+    /// count cleared lines and level up every so many,
+    /// and finish the game if that's the mode's goal reached
+    /// (counted as the piece locks, so a sprint's clock stops straight away)
+    fn add_lines(&mut self, cleared: u16) {
+        self.lines = self.lines.saturating_add(cleared);
+        let mode = self.settings.mode;
+        if mode.line_goal().is_some_and(|goal| self.lines >= goal) {
+            self.finish = Some(Finish::Cleared);
+        }
+
+        let level = 1 + self.lines / self.settings.lines_per_level.max(1);
+        if mode.levels_up() && level > self.level {
+            self.level = level;
+            self.gravity_speed = self.settings.gravity_curve.gravity(level);
+        }
+    }
+}
+
+/// a duration in ms to the nearest whole number of ticks
+pub(crate) fn ms_to_ticks(ms: u32) -> u32 {
+    // in u64 so nothing overflows however long it is
+    ((u64::from(ms) * u64::from(TICKS_PER_SECOND) + 500) / 1000) as u32
+}
+
+/// a number of ticks to the nearest ms, the other way from `ms_to_ticks`
+pub(crate) fn ticks_to_ms(ticks: u32) -> u32 {
+    ((u64::from(ticks) * 1000 + u64::from(TICKS_PER_SECOND / 2)) / u64::from(TICKS_PER_SECOND))
+        as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_input::mash;
+    use crate::{GameMode, GravityCurve, RandomizerKind};
+
+    /// build a game with the given rows stacked on the floor
+    /// rows are top to bottom, '#' is full, '.' is empty
+    fn game_with_rows(rows: &[&str]) -> Game {
+        let mut game = Game::new();
+        let top = GRID_VERTICAL_SIZE - 1 - rows.len();
+        for (y, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), GRID_HORIZONTAL_SIZE - 2, "bad row {:?}", row);
+            for (x, c) in row.chars().enumerate() {
+                game.grid[x + 1][top + y] = match c {
+                    '#' => GridSquare::Full,
+                    _ => GridSquare::Empty,
+                };
+            }
+        }
+        game
+    }
+
+    /// the bottom `count` rows of the playfield in the same format
+    fn bottom_rows(game: &Game, count: usize) -> Vec<String> {
+        let top = GRID_VERTICAL_SIZE - 1 - count;
+        (top..GRID_VERTICAL_SIZE - 1)
+            .map(|y| {
+                (1..GRID_HORIZONTAL_SIZE - 1)
+                    .map(|x| match game.grid[x][y] {
+                        GridSquare::Full => '#',
+                        GridSquare::Empty => '.',
+                        _ => '?',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_clears_pull_stack_down() {
+        // (name, board before, board after)
+        #[rustfmt::skip]
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            (
+                "single",
+                &[
+                    "#.........",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    "#.........",
+                ],
+            ),
+            (
+                "single under a tall stack",
+                &[
+                    ".#........",
+                    ".#........",
+                    "##.......#",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    ".#........",
+                    ".#........",
+                    "##.......#",
+                ],
+            ),
+            (
+                "double",
+                &[
+                    "..#.......",
+                    "##########",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    "..........",
+                    "..#.......",
+                ],
+            ),
+            (
+                "triple",
+                &[
+                    "...##.....",
+                    "##########",
+                    "##########",
+                    "##########",
+                    "#########.",
+                ],
+                &[
+                    "..........",
+                    "..........",
+                    "..........",
+                    "...##.....",
+                    "#########.",
+                ],
+            ),
+            (
+                "tetris",
+                &[
+                    "#........#",
+                    ".#......#.",
+                    "##########",
+                    "##########",
+                    "##########",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    "..........",
+                    "..........",
+                    "..........",
+                    "#........#",
+                    ".#......#.",
+                ],
+            ),
+            (
+                "split double",
+                &[
+                    "....#.....",
+                    "##########",
+                    "####.#####",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    "..........",
+                    "....#.....",
+                    "####.#####",
+                ],
+            ),
+            (
+                "split triple",
+                &[
+                    "#.........",
+                    "##########",
+                    ".#########",
+                    "##########",
+                    "##.#######",
+                    "##########",
+                ],
+                &[
+                    "..........",
+                    "..........",
+                    "..........",
+                    "#.........",
+                    ".#########",
+                    "##.#######",
+                ],
+            ),
+            (
+                "nothing to clear",
+                &[
+                    "#.........",
+                    "#########.",
+                ],
+                &[
+                    "#.........",
+                    "#########.",
+                ],
+            ),
+        ];
+
+        for (name, before, after) in cases {
+            let mut game = game_with_rows(before);
+            game.check_completion(Spin::None);
+            assert_eq!(game.line_to_delete, before != after, "{}", name);
+            game.delete_complete_lines();
+            assert_eq!(bottom_rows(&game, after.len()), *after, "{}", name);
+
+            // nothing should have fallen through the walls or floor
+            for j in 0..GRID_VERTICAL_SIZE {
+                assert_eq!(game.grid[0][j], GridSquare::Block, "{}", name);
+                assert_eq!(
+                    game.grid[GRID_HORIZONTAL_SIZE - 1][j],
+                    GridSquare::Block,
+                    "{}",
+                    name
+                );
+            }
+            for column in game.grid.iter() {
+                assert_eq!(
+                    column[GRID_VERTICAL_SIZE - 1],
+                    GridSquare::Block,
+                    "{}",
+                    name
+                );
+            }
+        }
+    }
+
+    /// put a piece in flight at x, y (top left of its box)
+    fn place(game: &mut Game, piece: Tetromino, rotation: Rotation, x: i32, y: i32) {
+        game.piece = piece;
+        game.piece_rotation = rotation;
+        game.piece_position_x = x;
+        game.piece_position_y = y;
+        game.piece_active = true;
+        assert!(game.piece_fits(x, y, rotation), "placed piece overlaps");
+    }
+
+    #[test]
+    fn rotation_without_kick() {
+        let mut game = Game::new();
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 5);
+        assert!(game.try_rotate(Rotation::Right));
+        assert_eq!(
+            (game.piece_position_x, game.piece_position_y),
+            (4, 5),
+            "nothing in the way so no kick"
+        );
+        assert_eq!(game.piece_rotation, Rotation::Right);
+    }
+
+    #[test]
+    fn rotation_in_every_direction() {
+        let mut game = Game::new();
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 5);
+        assert!(game.try_rotate(Rotation::Spawn.ccw()));
+        assert_eq!(game.piece_rotation, Rotation::Left);
+        assert!(game.try_rotate(Rotation::Left.flip()));
+        assert_eq!(game.piece_rotation, Rotation::Right);
+        assert!(game.try_rotate(Rotation::Right.cw()));
+        assert_eq!(game.piece_rotation, Rotation::Reverse);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (4, 5));
+    }
+
+    #[test]
+    fn half_turn_kicks_off_floor() {
+        // flat T sat on the floor has to move up a row to point down
+        let mut game = Game::new();
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 17);
+        assert!(game.try_rotate(Rotation::Reverse));
+        assert_eq!(game.piece_rotation, Rotation::Reverse);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (4, 16));
+        assert_eq!(
+            game.last_turn,
+            Some(Turn {
+                half: true,
+                kick: 1
+            })
+        );
+    }
+
+    #[test]
+    fn counter_clockwise_kicks_off_wall() {
+        // vertical I against the right wall turning back to flat, R -> 0 kicks
+        let mut game = Game::new();
+        place(&mut game, Tetromino::I, Rotation::Right, 8, 10);
+        assert!(game.try_rotate(Rotation::Spawn));
+        assert_eq!(game.piece_rotation, Rotation::Spawn);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (7, 10));
+    }
+
+    #[test]
+    fn i_piece_kicks_off_left_wall() {
+        // vertical I hugging the left wall, flat rotation would poke into it
+        let mut game = Game::new();
+        place(&mut game, Tetromino::I, Rotation::Left, 0, 10);
+        assert!(game.try_rotate(Rotation::Spawn));
+        assert_eq!(game.piece_rotation, Rotation::Spawn);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (1, 10));
+    }
+
+    #[test]
+    fn i_piece_kicks_off_right_wall() {
+        let mut game = Game::new();
+        place(&mut game, Tetromino::I, Rotation::Right, 8, 10);
+        assert!(game.try_rotate(Rotation::Reverse));
+        assert_eq!(game.piece_rotation, Rotation::Reverse);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (7, 10));
+    }
+
+    #[test]
+    fn t_spin_triple_uses_last_kick() {
+        // classic TST slot, the T has to drop two rows and shift left to get in
+        // the first four kicks are all blocked by the stack or the overhang
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            ".#........",
+            "..........",
+            "#.########",
+            "#..#######",
+            "#.########",
+        ]);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 2, 14);
+        assert!(game.try_rotate(Rotation::Right));
+        assert_eq!(game.piece_rotation, Rotation::Right);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (1, 16));
+
+        // lock it and clear the three lines, and it counts as a t-spin
+        let spin = game.lock_piece();
+        assert_eq!(spin, Spin::Full);
+        game.check_completion(spin);
+        assert_eq!(game.callout().and_then(Clear::name), Some("T-SPIN TRIPLE"));
+        game.delete_complete_lines();
+        assert_eq!(bottom_rows(&game, 2), [".#........", ".........."]);
+    }
+
+    #[test]
+    fn rotation_refused_when_no_kick_fits() {
+        // I in a one wide well can't go flat anywhere
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            "####.#####",
+            "####.#####",
+            "####.#####",
+            "####.#####",
+            "####.#####",
+            "####.#####",
+        ]);
+        place(&mut game, Tetromino::I, Rotation::Left, 4, 14);
+        assert!(!game.try_rotate(Rotation::Spawn));
+        assert_eq!(game.piece_rotation, Rotation::Left);
+        assert_eq!((game.piece_position_x, game.piece_position_y), (4, 14));
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        for kind in RandomizerKind::ALL {
+            let settings = Settings {
+                randomizer: kind,
+                ..Settings::default()
+            };
+            let mut a = Game::with_seed(settings, 42);
+            let mut b = Game::with_seed(settings, 42);
+            for (frame, input) in mash().take(5000).enumerate() {
+                a.step(input);
+                b.step(input);
+                assert!(
+                    a.grid == b.grid,
+                    "{:?} boards differ on frame {}",
+                    kind,
+                    frame
+                );
+                assert_eq!(
+                    a.piece_cells(),
+                    b.piece_cells(),
+                    "{:?} frame {}",
+                    kind,
+                    frame
+                );
+                assert_eq!(a.next_queue, b.next_queue);
+            }
+        }
+    }
+
+    #[test]
+    fn next_queue_feeds_spawns() {
+        for (asked, expected) in [(0, 1), (3, 3), (6, 6), (10, MAX_NEXT_QUEUE)] {
+            let settings = Settings {
+                next_queue: asked,
+                ..Settings::default()
+            };
+            let mut game = Game::with_seed(settings, 3);
+            assert_eq!(game.next_queue().count(), expected);
+
+            for _ in 0..20 {
+                let upcoming: Vec<_> = game.next_queue().collect();
+                game.create_piece();
+                assert_eq!(game.piece, upcoming[0]);
+                assert_eq!(
+                    game.next_queue().take(expected - 1).collect::<Vec<_>>(),
+                    upcoming[1..]
+                );
+                assert_eq!(game.next_queue().count(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn hold_swaps_once_per_piece() {
+        let mut game = Game::with_seed(Settings::default(), 9);
+        let hold = InputFrame {
+            hold: true,
+            ..InputFrame::default()
+        };
+        let nothing = InputFrame::default();
+
+        // first frame spawns a piece
+        game.step(nothing);
+        let first = game.piece;
+        let second = game.next_queue[0];
+
+        // empty slot, so the next piece comes out of the queue
+        game.step(hold);
+        assert_eq!(game.hold, Some(first));
+        assert_eq!(game.piece, second);
+        assert!(game.piece_active);
+
+        // can't swap back until this piece locks
+        game.step(nothing);
+        game.step(hold);
+        assert_eq!(game.hold, Some(first));
+        assert_eq!(game.piece, second);
+
+        // drop it on the floor and lock it, the next piece can swap again
+        game.piece_position_y = 20;
+        while !game.piece_fits(
+            game.piece_position_x,
+            game.piece_position_y,
+            game.piece_rotation,
+        ) {
+            game.piece_position_y -= 1;
+        }
+        game.lock_piece();
+        assert!(!game.piece_active);
+        game.step(nothing);
+        let third = game.piece;
+        game.step(hold);
+        assert_eq!(game.hold, Some(third));
+        assert_eq!(game.piece, first);
+        assert_eq!(game.piece_rotation, Rotation::Spawn);
+        assert_eq!(game.piece_position_y, 0);
+    }
+
+    #[test]
+    fn hard_drop_lands_and_locks() {
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            ".....#####",
+            "#########.",
+        ]);
+        place(&mut game, Tetromino::I, Rotation::Spawn, 1, 0);
+        game.step(InputFrame {
+            hard_drop: true,
+            ..InputFrame::default()
+        });
+
+        // lands on top of the stack, straight away, and scores 2 per row
+        assert!(!game.piece_active);
+        assert_eq!(bottom_rows(&game, 2), ["####.#####", "#########."]);
+        assert_eq!(game.score, 2 * 16);
+
+        // holding the button doesn't drop the next piece too
+        let held = InputFrame {
+            hard_drop: true,
+            ..InputFrame::default()
+        };
+        game.step(held);
+        game.step(held);
+        assert!(game.piece_active);
+        assert_eq!(game.piece_position_y, 0);
+    }
+
+    #[test]
+    fn ghost_sits_where_hard_drop_lands() {
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            "...#......",
+            "..###.....",
+            "#########.",
+        ]);
+        place(&mut game, Tetromino::T, Rotation::Reverse, 2, 3);
+        let ghost = game.ghost_cells().unwrap();
+        game.hard_drop();
+        assert_eq!(
+            ghost,
+            game.cells_at(
+                game.piece_position_x,
+                game.piece_position_y,
+                game.piece_rotation
+            )
+        );
+        assert_eq!(ghost, [(2, 15), (3, 15), (4, 15), (3, 16)]);
+    }
+
+    #[test]
+    fn lock_delay_waits_then_locks() {
+        let mut game = Game::new();
+        place(&mut game, Tetromino::O, Rotation::Spawn, 4, 17);
+        let lock_delay_ticks = ms_to_ticks(500);
+        for _ in 1..lock_delay_ticks {
+            game.step(InputFrame::default());
+            assert!(game.piece_active, "locked too early");
+        }
+        game.step(InputFrame::default());
+        assert!(!game.piece_active);
+        assert_eq!(game.grid[5][18], GridSquare::Full);
+    }
+
+    #[test]
+    fn moving_resets_lock_delay_up_to_a_limit() {
+        let mut game = Game::new();
+        place(&mut game, Tetromino::T, Rotation::Spawn, 3, 17);
+        let left = InputFrame {
+            left: true,
+            ..InputFrame::default()
+        };
+        let right = InputFrame {
+            right: true,
+            ..InputFrame::default()
+        };
+
+        // shuffle back and forth along the floor, each tap puts the lock delay back
+        // to zero until the resets run out and it locks as soon as it's on the ground
+        game.step(InputFrame::default());
+        let mut frames = 0;
+        let mut taps = 0;
+        while game.piece_active {
+            let input = match frames % 20 {
+                0 if taps % 2 == 0 => left,
+                0 => right,
+                _ => InputFrame::default(),
+            };
+            if input != InputFrame::default() {
+                taps += 1;
+            }
+            game.step(input);
+            frames += 1;
+            assert!(frames < 1000, "never locked");
+        }
+        assert_eq!(taps, 15);
+        assert!(frames > 14 * 20, "lock delay wasn't reset");
+    }
+
+    #[test]
+    fn auto_shift_after_delay_then_repeats() {
+        let settings = Settings {
+            das_ms: 100,
+            arr_ms: 50,
+            ..Settings::default()
+        };
+        let mut game = Game::with_seed(settings, 0);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 0);
+        let right = InputFrame {
+            right: true,
+            ..InputFrame::default()
+        };
+
+        // (frames held, column) - moves on the press, again once the 6 frame
+        // delay is up, then every 3 frames until it hits the wall
+        let expected = [(1, 5), (6, 5), (7, 6), (9, 6), (10, 7), (13, 8), (16, 8)];
+        let mut frame = 0;
+        for (held, x) in expected {
+            while frame < held {
+                game.step(right);
+                frame += 1;
+            }
+            assert_eq!(game.piece_position_x, x, "after {} frames", held);
+        }
+    }
+
+    #[test]
+    fn default_handling_rounds_to_the_nearest_tick() {
+        // 167 ms is 10.02 ticks and 33 ms is 1.98, so 10 and 2 rather than 10 and 1
+        assert_eq!(ms_to_ticks(167), 10);
+        assert_eq!(ms_to_ticks(33), 2);
+
+        let mut game = Game::with_seed(Settings::default(), 0);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 0);
+        let right = InputFrame {
+            right: true,
+            ..InputFrame::default()
+        };
+        // (frames held, column) - moves on the press, again once the 10 frame
+        // delay is up, then every other frame
+        let expected = [(1, 5), (10, 5), (11, 6), (12, 6), (13, 7), (14, 7), (15, 8)];
+        let mut frame = 0;
+        for (held, x) in expected {
+            while frame < held {
+                game.step(right);
+                frame += 1;
+            }
+            assert_eq!(game.piece_position_x, x, "after {} frames", held);
+        }
+    }
+
+    #[test]
+    fn zero_repeat_rate_goes_straight_to_the_wall() {
+        let settings = Settings {
+            das_ms: 100,
+            arr_ms: 0,
+            ..Settings::default()
+        };
+        let mut game = Game::with_seed(settings, 0);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 0);
+        let left = InputFrame {
+            left: true,
+            ..InputFrame::default()
+        };
+        for _ in 0..6 {
+            game.step(left);
+        }
+        assert_eq!(
+            game.piece_position_x, 3,
+            "only the first tap before the delay"
+        );
+        game.step(left);
+        assert_eq!(game.piece_position_x, 1);
+    }
+
+    #[test]
+    fn level_up_every_ten_lines() {
+        let mut game = Game::new();
+        let start = game.gravity_speed;
+        // (lines cleared at once, level after)
+        for (cleared, level) in [(4, 1), (4, 1), (1, 1), (1, 2), (3, 2), (4, 2), (3, 3)] {
+            game.add_lines(cleared);
+            assert_eq!(game.level, level, "at {} lines", game.lines);
+        }
+        assert_eq!(game.lines, 20);
+        assert!(game.gravity_speed > start);
+        assert_eq!(game.gravity_speed, GravityCurve::Guideline.gravity(3));
+    }
+
+    #[test]
+    fn lines_count_every_row_cleared() {
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            "##########",
+            "#########.",
+            "##########",
+        ]);
+        game.check_completion(Spin::None);
+        game.delete_complete_lines();
+        assert_eq!(game.lines, 2);
+    }
+
+    #[test]
+    fn soft_dropped_tetris_perfect_clear_scores() {
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ]);
+        place(&mut game, Tetromino::I, Rotation::Right, 8, 0);
+
+        // soft drop all the way down, a point a row
+        let input = InputFrame {
+            soft_drop: true,
+            ..InputFrame::default()
+        };
+        while game.piece_active {
+            game.step(input);
+        }
+        // and clears the whole board
+        assert_eq!(game.score, 15 * SOFT_DROP_POINTS + 800 + 2000);
+        assert!(game.line_to_delete);
+    }
+
+    #[test]
+    fn t_spin_double_under_an_overhang() {
+        #[rustfmt::skip]
+        let mut game = game_with_rows(&[
+            "...#......",
+            "###...####",
+            "####.#####",
+        ]);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 16);
+        assert!(game.try_rotate(Rotation::Reverse));
+        let spin = game.lock_piece();
+        assert_eq!(spin, Spin::Full);
+        game.check_completion(spin);
+        assert_eq!(game.callout().and_then(Clear::name), Some("T-SPIN DOUBLE"));
+        assert_eq!(game.score, 1200);
+    }
+
+    #[test]
+    fn mini_t_spin_unless_it_took_the_big_kick() {
+        #[rustfmt::skip]
+        let rows = [
+            "...#......",
+            "###...####",
+        ];
+        // turned in place, only one of the corners it points at is filled
+        let mut game = game_with_rows(&rows);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 4, 17);
+        game.last_turn = Some(Turn {
+            half: false,
+            kick: 0,
+        });
+        assert_eq!(game.spin(), Spin::Mini);
+
+        // the same spot reached with the last kick is a full one
+        game.last_turn = Some(Turn {
+            half: false,
+            kick: T_SPIN_KICK,
+        });
+        assert_eq!(game.spin(), Spin::Full);
+
+        // but not with a half turn's kick, even one that's (1, 2) as well
+        for kick in [2, T_SPIN_KICK] {
+            game.last_turn = Some(Turn { half: true, kick });
+            assert_eq!(game.spin(), Spin::Mini);
+        }
+
+        // and it isn't a spin at all if it moved after turning
+        let mut game = game_with_rows(&rows);
+        place(&mut game, Tetromino::T, Rotation::Spawn, 5, 10);
+        game.last_turn = Some(Turn {
+            half: false,
+            kick: 0,
+        });
+        assert!(game.shift(-1));
+        game.piece_position_y = 17;
+        assert_eq!(game.spin(), Spin::None);
+
+        // nor if it isn't a T
+        let mut game = game_with_rows(&rows);
+        place(&mut game, Tetromino::L, Rotation::Spawn, 4, 17);
+        game.last_turn = Some(Turn {
+            half: false,
+            kick: 0,
+        });
+        assert_eq!(game.spin(), Spin::None);
+    }
+
+    #[test]
+    fn sprint_ends_at_forty_lines_without_levelling_up() {
+        let settings = Settings {
+            mode: GameMode::Sprint,
+            ..Settings::default()
+        };
+        let mut game = Game::with_seed(settings, 1);
+        for _ in 0..9 {
+            game.add_lines(4);
+        }
+        assert_eq!((game.finish(), game.level), (None, 1));
+        game.add_lines(4);
+        assert_eq!(game.finish(), Some(Finish::Cleared));
+        assert_eq!(game.level, 1);
+
+        // and the clock stops
+        let elapsed = game.elapsed();
+        game.step(InputFrame::default());
+        assert_eq!(game.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn marathon_goal_and_endless() {
+        let mut game = Game::new();
+        game.add_lines(149);
+        assert_eq!(game.finish(), None);
+        game.add_lines(1);
+        assert_eq!(game.finish(), Some(Finish::Cleared));
+
+        let mut game = Game::with_settings(Settings {
+            mode: GameMode::Endless,
+            ..Settings::default()
+        });
+        game.add_lines(1000);
+        assert_eq!(game.finish(), None);
+    }
+
+    #[test]
+    fn ultra_ends_after_two_minutes() {
+        let settings = Settings {
+            mode: GameMode::Ultra,
+            ..Settings::default()
+        };
+        let mut game = Game::with_seed(settings, 1);
+        game.step(InputFrame::default());
+        assert_eq!(game.time_left(), Some(ticks_to_duration(120 * 60 - 1)));
+
+        // skip to the last tick
+        game.ticks = 120 * TICKS_PER_SECOND - 1;
+        assert_eq!(game.finish(), None);
+        assert_eq!(game.time_left(), Some(ticks_to_duration(1)));
+        game.step(InputFrame::default());
+        assert_eq!(game.finish(), Some(Finish::TimeUp));
+        assert_eq!(game.time_left(), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::TICKS_PER_SECOND;
+
+/// gravity is counted in fractions of a row per tick, this much is a whole row
+pub const GRAVITY_ONE: u32 = 1 << 16;
+
+// fastest anything falls, 20 rows a tick is the whole board at once
+const MAX_GRAVITY: u32 = 20 * GRAVITY_ONE;
+
+// ticks per row for each nes level (starting from our level 1),
+// anything past the end of the table goes at the last speed
+const NES_TICKS_PER_ROW: [u32; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+
+// the guideline formula only goes up to here, past it the base goes
+// negative and it'd flip between fast and slow
+const GUIDELINE_MAX_LEVEL: u32 = 20;
+
+/// how fast pieces fall as the levels go up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GravityCurve {
+    /// the tetris guideline formula, (0.8 - (level - 1) * 0.007) ^ (level - 1)
+    /// seconds per row
+    #[default]
+    Guideline,
+    /// the nes frame table
+    Nes,
+}
+
+impl GravityCurve {
+    pub const ALL: [GravityCurve; 2] = [GravityCurve::Guideline, GravityCurve::Nes];
+
+    /// short name used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            GravityCurve::Guideline => "guideline",
+            GravityCurve::Nes => "nes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GravityCurve> {
+        GravityCurve::ALL
+            .into_iter()
+            .find(|curve| curve.name() == name)
+    }
+
+    /// rows fallen per tick at a level, in `GRAVITY_ONE`ths of a row
+    pub fn gravity(self, level: u16) -> u32 {
+        let level = u32::from(level.max(1));
+        let gravity = match self {
+            GravityCurve::Guideline => {
+                // multiply it out by hand rather than powf so it comes out
+                // the same on every platform, replays rely on it
+                let level = level.min(GUIDELINE_MAX_LEVEL);
+                let base = 0.8 - f64::from(level - 1) * 0.007;
+                let seconds_per_row = (1..level).fold(1.0, |acc, _| acc * base);
+                let rows_per_tick = 1.0 / (seconds_per_row * f64::from(TICKS_PER_SECOND));
+                (rows_per_tick * f64::from(GRAVITY_ONE)).round() as u32
+            }
+            GravityCurve::Nes => {
+                let index = (level as usize - 1).min(NES_TICKS_PER_ROW.len() - 1);
+                GRAVITY_ONE / NES_TICKS_PER_ROW[index]
+            }
+        };
+        gravity.clamp(1, MAX_GRAVITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guideline_matches_published_speeds() {
+        // (level, seconds per row) from the guideline table
+        let table = [
+            (1, 1.0),
+            (2, 0.793),
+            (5, 0.3552),
+            (10, 0.06415),
+            (15, 0.00706),
+        ];
+        for (level, seconds) in table {
+            let ticks_per_row =
+                f64::from(GRAVITY_ONE) / f64::from(GravityCurve::Guideline.gravity(level));
+            let expected = seconds * f64::from(TICKS_PER_SECOND);
+            assert!(
+                (ticks_per_row - expected).abs() < expected * 0.01,
+                "level {} takes {} ticks per row, expected {}",
+                level,
+                ticks_per_row,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn nes_uses_frame_table() {
+        assert_eq!(GravityCurve::Nes.gravity(1), GRAVITY_ONE / 48);
+        assert_eq!(GravityCurve::Nes.gravity(10), GRAVITY_ONE / 6);
+        assert_eq!(GravityCurve::Nes.gravity(29), GRAVITY_ONE / 2);
+        // the famous kill screen speed, and it never gets past it
+        assert_eq!(GravityCurve::Nes.gravity(30), GRAVITY_ONE);
+        assert_eq!(GravityCurve::Nes.gravity(100), GRAVITY_ONE);
+    }
+
+    #[test]
+    fn guideline_stays_at_top_speed_past_level_20() {
+        for level in [20, 116, 117, 200, u16::MAX] {
+            assert_eq!(GravityCurve::Guideline.gravity(level), MAX_GRAVITY);
+        }
+    }
+
+    #[test]
+    fn never_gets_slower() {
+        for curve in GravityCurve::ALL {
+            for level in 1..1000 {
+                assert!(
+                    curve.gravity(level + 1) >= curve.gravity(level),
+                    "{:?} slows down at level {}",
+                    curve,
+                    level + 1
+                );
+                assert!(curve.gravity(level) <= MAX_GRAVITY);
+            }
+        }
+    }
+}
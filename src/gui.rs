@@ -0,0 +1,905 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use raylib::prelude::*;
+use raytris::{
+    format_date, format_time, App, Command, Entry, FileError, Finish, FixedTimestep, Game,
+    GameMode, GravityCurve, GridSquare, InputFrame, Leaderboard, MenuKey, Playback, RandomizerKind,
+    Replay, Rotation, Screen, Settings, Tetromino, GRID_HORIZONTAL_SIZE, GRID_VERTICAL_SIZE,
+    MAX_NEXT_QUEUE,
+};
+
+const SQUARE_SIZE: i32 = 20;
+const SCREEN_WIDTH: i32 = 600;
+const SCREEN_HEIGHT: i32 = 450;
+// where saved replays go, relative to wherever the game's run from, except
+// the ones linked from the leaderboard which go in the same-named folder
+// beside it
+const REPLAY_DIR: &str = "replays";
+// the leaderboard and any unfinished game are kept in the platform's
+// data folder, under this
+const DATA_DIR_NAME: &str = "raytris";
+const LEADERBOARD_FILE: &str = "leaderboard.json";
+// an unfinished game's kept here when the window's closed, until it's continued
+const SAVE_FILE: &str = "save.json";
+
+/// convert a core colour to a raylib one
+fn rl_color(color: raytris::Color) -> Color {
+    Color::new(color.r, color.g, color.b, color.a)
+}
+
+/// read the keyboard into the input frame the core understands
+fn read_input(rl: &RaylibHandle) -> InputFrame {
+    InputFrame {
+        left: rl.is_key_down(KeyboardKey::KEY_LEFT),
+        right: rl.is_key_down(KeyboardKey::KEY_RIGHT),
+        rotate_cw: rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_X),
+        rotate_ccw: rl.is_key_down(KeyboardKey::KEY_Z)
+            || rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL),
+        rotate_180: rl.is_key_down(KeyboardKey::KEY_A),
+        soft_drop: rl.is_key_down(KeyboardKey::KEY_DOWN),
+        hard_drop: rl.is_key_down(KeyboardKey::KEY_SPACE),
+        hold: rl.is_key_down(KeyboardKey::KEY_C) || rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT),
+    }
+}
+
+/// draw the outline of an empty square
+fn draw_empty_square(d: &mut RaylibDrawHandle, ox: i32, oy: i32) {
+    d.draw_line(ox, oy, ox + SQUARE_SIZE, oy, Color::LIGHTGRAY);
+    d.draw_line(ox, oy, ox, oy + SQUARE_SIZE, Color::LIGHTGRAY);
+    d.draw_line(
+        ox + SQUARE_SIZE,
+        oy,
+        ox + SQUARE_SIZE,
+        oy + SQUARE_SIZE,
+        Color::LIGHTGRAY,
+    );
+    d.draw_line(
+        ox,
+        oy + SQUARE_SIZE,
+        ox + SQUARE_SIZE,
+        oy + SQUARE_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// draw just the squares of a piece in its spawn rotation
+fn draw_piece(d: &mut RaylibDrawHandle, piece: Tetromino, ox: i32, oy: i32, size: i32) {
+    for (i, j) in piece.cells(Rotation::Spawn) {
+        d.draw_rectangle(
+            ox + i * size,
+            oy + j * size,
+            size,
+            size,
+            rl_color(piece.color()),
+        );
+    }
+}
+
+/// draw a 4x4 box with a piece in its spawn rotation, or nothing in it
+fn draw_piece_box(
+    d: &mut RaylibDrawHandle,
+    piece: Option<Tetromino>,
+    greyed: bool,
+    ox: i32,
+    oy: i32,
+) {
+    let cells = piece.map(|piece| piece.cells(Rotation::Spawn));
+    let color = match piece {
+        Some(_) if greyed => Color::GRAY,
+        Some(piece) => rl_color(piece.color()),
+        None => Color::GRAY,
+    };
+    for j in 0..4 {
+        for i in 0..4 {
+            let x = ox + i * SQUARE_SIZE;
+            let y = oy + j * SQUARE_SIZE;
+            if cells.is_some_and(|cells| cells.contains(&(i, j))) {
+                d.draw_rectangle(x, y, SQUARE_SIZE, SQUARE_SIZE, color);
+            } else {
+                draw_empty_square(d, x, y);
+            }
+        }
+    }
+}
+
+/// the board, pieces and stats
+fn draw_game(d: &mut RaylibDrawHandle, game: &Game) {
+    // flash completed lines while they're being deleted
+    // todo: magic numbers
+    let fading_colour = if game.fade_line_counter() % 8 < 4 {
+        Color::MAROON
+    } else {
+        Color::GRAY
+    };
+
+    // draw gameplay area
+    // todo: should have an int vector (just struct w two fields, don't need math)
+    let mut offset = Vector2 {
+        x: SCREEN_WIDTH as f32 / 2.0 - GRID_HORIZONTAL_SIZE as f32 * SQUARE_SIZE as f32 - 50.0, // todo: very magic numbers
+        y: SCREEN_HEIGHT as f32 / 2.0 - (GRID_VERTICAL_SIZE - 1) as f32 * SQUARE_SIZE as f32 / 2.0
+            + SQUARE_SIZE as f32 * 2.0
+            - 50.0,
+    };
+
+    let grid_x = offset.x as i32;
+    let grid_y = offset.y as i32;
+    let controller = offset.x;
+
+    let grid = game.grid();
+    for j in 0..GRID_VERTICAL_SIZE {
+        for column in grid.iter() {
+            // draw each square of the grid
+            let ox = offset.x as i32;
+            let oy = offset.y as i32;
+            match column[j] {
+                GridSquare::Empty => draw_empty_square(d, ox, oy),
+                GridSquare::Full => d.draw_rectangle(ox, oy, SQUARE_SIZE, SQUARE_SIZE, Color::GRAY),
+                GridSquare::Block => {
+                    d.draw_rectangle(ox, oy, SQUARE_SIZE, SQUARE_SIZE, Color::LIGHTGRAY)
+                }
+                GridSquare::Fading => {
+                    d.draw_rectangle(ox, oy, SQUARE_SIZE, SQUARE_SIZE, fading_colour)
+                }
+            }
+            offset.x += SQUARE_SIZE as f32;
+        }
+        offset.x = controller;
+        offset.y += SQUARE_SIZE as f32;
+    }
+
+    // outline where the piece would land, under the piece itself
+    if game.settings().ghost {
+        if let Some(cells) = game.ghost_cells() {
+            for (i, j) in cells {
+                d.draw_rectangle_lines(
+                    grid_x + i * SQUARE_SIZE,
+                    grid_y + j * SQUARE_SIZE,
+                    SQUARE_SIZE,
+                    SQUARE_SIZE,
+                    rl_color(game.piece().color()).fade(0.5),
+                );
+            }
+        }
+    }
+
+    // draw the piece in flight over the top of the grid
+    if let Some(cells) = game.piece_cells() {
+        for (i, j) in cells {
+            d.draw_rectangle(
+                grid_x + i * SQUARE_SIZE,
+                grid_y + j * SQUARE_SIZE,
+                SQUARE_SIZE,
+                SQUARE_SIZE,
+                rl_color(game.piece().color()),
+            );
+        }
+    }
+
+    // draw hold slot (hard-coded), greyed out once it's been used
+    d.draw_text("HOLD:", 300, 25, 10, Color::GRAY);
+    draw_piece_box(d, game.hold(), game.hold_used(), 300, 45);
+
+    // shout about t-spins, tetrises and perfect clears under the hold box
+    if let Some(clear) = game.callout() {
+        if let Some(name) = clear.name() {
+            d.draw_text(name, 300, 150, 20, Color::PURPLE);
+        }
+        if clear.perfect {
+            d.draw_text("PERFECT CLEAR", 300, 175, 20, Color::GOLD);
+        }
+    }
+
+    // draw next queue (hard-coded), the very next piece gets a full size box
+    // and the rest stack up underneath at half size
+    offset.x = 500_f32;
+    offset.y = 45_f32;
+
+    let mut next_queue = game.next_queue();
+    draw_piece_box(
+        d,
+        next_queue.next(),
+        false,
+        offset.x as i32,
+        offset.y as i32,
+    );
+    offset.y += 4.0 * SQUARE_SIZE as f32;
+
+    let mut queue_y = offset.y as i32 + SQUARE_SIZE / 2;
+    for piece in next_queue {
+        draw_piece(d, piece, offset.x as i32, queue_y, SQUARE_SIZE / 2);
+        // every piece fits in its top two rows when flat
+        queue_y += SQUARE_SIZE / 2 * 3;
+    }
+
+    let ox = offset.x as i32;
+    let oy = offset.y as i32;
+
+    // text
+    d.draw_text("NEXT:", ox, oy - 100, 10, Color::GRAY);
+    let stats = stats(game);
+    for (n, line) in stats.iter().enumerate() {
+        d.draw_text(line, ox, queue_y + 20 + n as i32 * 15, 10, Color::GRAY);
+    }
+    let text_y = queue_y + 20 + stats.len() as i32 * 15;
+    // so a run can be reproduced with --seed
+    d.draw_text(
+        &format!("SEED:      {}", game.seed()),
+        ox,
+        text_y,
+        10,
+        Color::LIGHTGRAY,
+    );
+    if let Some(combo @ 1..) = game.scoring().combo() {
+        d.draw_text(
+            &format!("COMBO x{}", combo),
+            ox,
+            text_y + 20,
+            10,
+            Color::DARKGRAY,
+        );
+    }
+    if game.scoring().back_to_back() {
+        d.draw_text("BACK TO BACK", ox, text_y + 35, 10, Color::DARKGRAY);
+    }
+}
+
+/// the numbers that matter for the mode being played, most important first
+fn stats(game: &Game) -> Vec<String> {
+    let mode = game.settings().mode;
+    let score = format!("SCORE:     {}", game.score());
+    let lines = match mode.line_goal() {
+        Some(goal) => format!("LINES:     {}/{}", game.lines(), goal),
+        None => format!("LINES:     {}", game.lines()),
+    };
+    let level = format!("LEVEL:     {}", game.level());
+    match mode {
+        GameMode::Marathon | GameMode::Endless => vec![score, lines, level],
+        GameMode::Sprint => vec![
+            format!("TIME:      {}", format_time(game.elapsed())),
+            lines,
+            score,
+        ],
+        GameMode::Ultra => vec![
+            format!(
+                "TIME:      {}",
+                format_time(game.time_left().unwrap_or_default())
+            ),
+            score,
+            lines,
+        ],
+    }
+}
+
+/// a heading with a list of choices under it, the selected one picked out
+fn draw_menu<'a>(
+    d: &mut RaylibDrawHandle,
+    heading: &str,
+    items: impl Iterator<Item = (&'a str, Option<String>, bool)>,
+) {
+    d.draw_text(heading, 200, 100, 40, Color::GRAY);
+    for (n, (label, value, selected)) in items.enumerate() {
+        let y = 170 + n as i32 * 30;
+        let color = if selected { Color::MAROON } else { Color::GRAY };
+        if selected {
+            d.draw_text(">", 180, y, 20, color);
+        }
+        d.draw_text(label, 200, y, 20, color);
+        if let Some(value) = value {
+            d.draw_text(&value, 400, y, 20, color);
+        }
+    }
+}
+
+/// the final stats under the results menu
+fn draw_results(d: &mut RaylibDrawHandle, game: &Game) {
+    let mut stats = stats(game);
+    stats.insert(0, format!("MODE:      {}", game.settings().mode.label()));
+    for (n, line) in stats.iter().enumerate() {
+        d.draw_text(line, 200, 260 + n as i32 * 25, 20, Color::DARKGRAY);
+    }
+}
+
+/// the leaderboard for a mode, best first
+fn draw_high_scores(d: &mut RaylibDrawHandle, leaderboard: &Leaderboard, mode: GameMode) {
+    d.draw_text("HIGH SCORES", 200, 60, 40, Color::GRAY);
+    d.draw_text(
+        &format!("< {} >", mode.label()),
+        200,
+        110,
+        20,
+        Color::MAROON,
+    );
+    let mut entries = leaderboard.top(mode).peekable();
+    if entries.peek().is_none() {
+        d.draw_text("NOTHING YET", 200, 160, 20, Color::LIGHTGRAY);
+    }
+    for (n, entry) in entries.enumerate() {
+        // sprints are about the time, everything else about the score
+        let result = match mode {
+            GameMode::Sprint => format_time(entry.time()),
+            _ => entry.score.to_string(),
+        };
+        let line = format!(
+            "{:>2}. {:<12} {:>9}  {:>3} LINES  LV {:>2}  {}  {}{}",
+            n + 1,
+            entry.name,
+            result,
+            entry.lines,
+            entry.level,
+            format_time(entry.time()),
+            format_date(entry.date),
+            if entry.replay.is_some() { "  R" } else { "" },
+        );
+        d.draw_text(&line, 60, 160 + n as i32 * 22, 10, Color::DARKGRAY);
+    }
+    d.draw_text(
+        "[LEFT]/[RIGHT] MODE  [ESC] BACK  R = REPLAY SAVED",
+        180,
+        420,
+        10,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// what's playing and how, over the top of a replay
+fn draw_replay_hud(d: &mut RaylibDrawHandle, playback: &Playback, seek_entry: &str) {
+    let state = if playback.is_finished() {
+        "END".to_string()
+    } else if playback.is_paused() {
+        "PAUSED".to_string()
+    } else {
+        format!("X{}", playback.speed())
+    };
+    d.draw_text(&format!("REPLAY  {}", state), 20, 10, 20, Color::MAROON);
+    d.draw_text(
+        &format!(
+            "PIECE {}/{}   {}",
+            playback.piece(),
+            playback.piece_count(),
+            format_time(playback.game().elapsed())
+        ),
+        20,
+        35,
+        10,
+        Color::DARKGRAY,
+    );
+    if !seek_entry.is_empty() {
+        d.draw_text(
+            &format!("GO TO PIECE {}_", seek_entry),
+            20,
+            50,
+            10,
+            Color::MAROON,
+        );
+    }
+    d.draw_text(
+        "[SPACE] PAUSE  [UP]/[DOWN] SPEED  [RIGHT] STEP  [ [ ]/[ ] ] PIECE  [0-9] [ENTER] GO TO PIECE  [ESC] EXIT",
+        20,
+        460,
+        10,
+        Color::LIGHTGRAY,
+    );
+}
+
+fn draw(
+    app: &App,
+    game: &Game,
+    leaderboard: &Leaderboard,
+    playback: Option<(&Playback, &str)>,
+    notice: Option<&str>,
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+) {
+    let mut d = rl.begin_drawing(thread);
+
+    d.clear_background(Color::WHITE);
+
+    match app.screen() {
+        Screen::Title(menu) => draw_menu(
+            &mut d,
+            "RAYTRIS",
+            menu.items()
+                .map(|(item, selected)| (item.label(), None, selected)),
+        ),
+        Screen::ModeSelect(menu) => draw_menu(
+            &mut d,
+            "MODE",
+            menu.items()
+                .map(|(mode, selected)| (mode.label(), None, selected)),
+        ),
+        Screen::Playing => draw_game(&mut d, game),
+        // the board stays hidden while paused, no planning ahead
+        Screen::Paused(menu) => draw_menu(
+            &mut d,
+            "GAME PAUSED",
+            menu.items()
+                .map(|(item, selected)| (item.label(), None, selected)),
+        ),
+        Screen::Settings { menu, .. } => {
+            let settings = app.settings();
+            draw_menu(
+                &mut d,
+                "SETTINGS",
+                menu.items()
+                    .map(|(item, selected)| (item.label(), Some(settings.value(item)), selected)),
+            );
+            d.draw_text(
+                "[LEFT]/[RIGHT] TO CHANGE, APPLIES FROM THE NEXT GAME",
+                180,
+                420,
+                10,
+                Color::LIGHTGRAY,
+            );
+        }
+        Screen::NameEntry => {
+            d.draw_text("NEW HIGH SCORE!", 200, 100, 40, Color::GRAY);
+            d.draw_text(
+                &format!("NAME: {}_", app.name()),
+                200,
+                170,
+                20,
+                Color::MAROON,
+            );
+            draw_results(&mut d, game);
+            d.draw_text(
+                "TYPE A NAME, [ENTER] TO SAVE, [ESC] TO SKIP",
+                200,
+                420,
+                10,
+                Color::LIGHTGRAY,
+            );
+        }
+        Screen::HighScores(mode) => draw_high_scores(&mut d, leaderboard, *mode),
+        Screen::Results(menu) => {
+            let heading = match game.finish() {
+                Some(Finish::Cleared) => "COMPLETE!",
+                Some(Finish::TimeUp) => "TIME'S UP",
+                Some(Finish::ToppedOut) | None => "GAME OVER",
+            };
+            draw_menu(
+                &mut d,
+                heading,
+                menu.items()
+                    .map(|(item, selected)| (item.label(), None, selected)),
+            );
+            draw_results(&mut d, game);
+            if let Some(notice) = notice {
+                d.draw_text(notice, 200, 400, 10, Color::DARKGRAY);
+            }
+        }
+        Screen::Replay => {
+            if let Some((playback, seek_entry)) = playback {
+                draw_game(&mut d, playback.game());
+                draw_replay_hud(&mut d, playback, seek_entry);
+            }
+        }
+    }
+}
+
+/// handle the replay controls for a frame, the digits typed so far towards
+/// a piece number to jump to are kept in seek_entry
+fn control_playback(rl: &mut RaylibHandle, playback: &mut Playback, seek_entry: &mut String) {
+    if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+        playback.toggle_pause();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+        playback.faster();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+        playback.slower();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+        playback.step_frame();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+        playback.seek_piece(playback.piece() + 1);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+        playback.seek_piece(playback.piece().saturating_sub(1));
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+        playback.seek_tick(0);
+    }
+
+    // type a piece number and press enter to jump to it
+    while let Some(key) = rl.get_key_pressed() {
+        let digit = key as u32;
+        if (KeyboardKey::KEY_ZERO as u32..=KeyboardKey::KEY_NINE as u32).contains(&digit)
+            && seek_entry.len() < 6
+        {
+            seek_entry.push(char::from(
+                b'0' + (digit - KeyboardKey::KEY_ZERO as u32) as u8,
+            ));
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        seek_entry.pop();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+        if let Ok(piece) = seek_entry.parse() {
+            playback.seek_piece(piece);
+        }
+        seek_entry.clear();
+    }
+}
+
+/// typing a name for the leaderboard, where letters go to the name
+/// and only enter and escape work as menu buttons
+fn read_name_keys(rl: &mut RaylibHandle, app: &mut App) -> Vec<MenuKey> {
+    while let Some(key) = rl.get_key_pressed_number() {
+        // letters, numbers and space have the same codes as in ascii
+        if let Some(c) = char::from_u32(key) {
+            app.type_name(c);
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        app.erase_name();
+    }
+    let bindings = [
+        (KeyboardKey::KEY_ENTER, MenuKey::Confirm),
+        (KeyboardKey::KEY_ESCAPE, MenuKey::Back),
+    ];
+    bindings
+        .iter()
+        .filter(|&&(key, _)| rl.is_key_pressed(key))
+        .map(|&(_, menu_key)| menu_key)
+        .collect()
+}
+
+/// menu buttons pressed this frame
+fn read_menu_keys(rl: &RaylibHandle) -> Vec<MenuKey> {
+    let bindings = [
+        (KeyboardKey::KEY_UP, MenuKey::Up),
+        (KeyboardKey::KEY_DOWN, MenuKey::Down),
+        (KeyboardKey::KEY_LEFT, MenuKey::Left),
+        (KeyboardKey::KEY_RIGHT, MenuKey::Right),
+        (KeyboardKey::KEY_ENTER, MenuKey::Confirm),
+        (KeyboardKey::KEY_ESCAPE, MenuKey::Back),
+        (KeyboardKey::KEY_P, MenuKey::Pause),
+    ];
+    bindings
+        .iter()
+        .filter(|&&(key, _)| rl.is_key_pressed(key))
+        .map(|&(_, menu_key)| menu_key)
+        .collect()
+}
+
+/// write a game's replay into a replays folder, named so they sort by mode
+/// and don't overwrite each other
+fn save_replay(game: &Game, dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "{}-{}-{}.rtr",
+        game.settings().mode.name(),
+        game.seed(),
+        unix_time()
+    ));
+    game.replay().save(&path)?;
+    Ok(path)
+}
+
+/// seconds since the unix epoch
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// where one of our files lives, if the platform has somewhere for them
+fn data_path(file: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(DATA_DIR_NAME).join(file))
+}
+
+/// the leaderboard as it was left, or an empty one the first time. a corrupt
+/// one is moved out of the way rather than written over, but one that just
+/// can't be read right now (another version's, say) is left alone and
+/// nothing's saved over it, which is what `None` means
+fn load_leaderboard(path: &Path) -> Option<Leaderboard> {
+    match Leaderboard::load(path) {
+        Ok(leaderboard) => Some(leaderboard),
+        Err(FileError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+            Some(Leaderboard::new())
+        }
+        Err(FileError::Corrupt(error)) => {
+            let backup = path.with_extension("json.bad");
+            eprintln!(
+                "couldn't load {}, moving it to {}: {}",
+                path.display(),
+                backup.display(),
+                error
+            );
+            if let Err(error) = fs::rename(path, &backup) {
+                eprintln!("couldn't move it: {}", error);
+                return None;
+            }
+            Some(Leaderboard::new())
+        }
+        Err(error) => {
+            eprintln!(
+                "couldn't load {}, high scores won't be saved: {}",
+                path.display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+/// put a finished game on the leaderboard, with its replay saved alongside,
+/// and say how it went
+fn record_score(
+    game: &Game,
+    name: &str,
+    leaderboard: &mut Leaderboard,
+    path: Option<&Path>,
+) -> String {
+    let mut entry = match Entry::new(game, name.trim().to_string(), unix_time()) {
+        Some(entry) => entry,
+        None => return "THAT GAME CAN'T GO ON THE LEADERBOARD".to_string(),
+    };
+    // kept with the leaderboard so the link doesn't depend on where the game's run from
+    entry.replay = data_path(REPLAY_DIR).and_then(|dir| save_replay(game, &dir).ok());
+    let place = match leaderboard.add(entry) {
+        Some(place) => place,
+        None => return "NOT QUITE ENOUGH FOR THE LEADERBOARD".to_string(),
+    };
+    match path.map(|path| leaderboard.save(path)) {
+        Some(Ok(())) => format!(
+            "#{} ON THE {} LEADERBOARD",
+            place + 1,
+            game.settings().mode.label()
+        ),
+        Some(Err(error)) => format!("COULDN'T SAVE HIGH SCORE: {}", error),
+        None => "HIGH SCORES AREN'T BEING SAVED".to_string(),
+    }
+}
+
+/// command line options
+struct Args {
+    settings: Settings,
+    // same seed every game if given, otherwise a fresh one each time
+    seed: Option<u64>,
+    // watch this replay instead of going to the title screen
+    replay: Option<PathBuf>,
+}
+
+/// the number following a command line option
+fn number<T: std::str::FromStr>(iter: &mut impl Iterator<Item = String>, arg: &str) -> Result<T> {
+    let value = iter.next().ok_or_else(|| eyre!("{} needs a value", arg))?;
+    value
+        .parse()
+        .map_err(|_| eyre!("{} should be a number, got {:?}", arg, value))
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        settings: Settings::default(),
+        seed: None,
+        replay: None,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--randomizer" => {
+                let name = iter
+                    .next()
+                    .ok_or_else(|| eyre!("--randomizer needs a value"))?;
+                args.settings.randomizer = RandomizerKind::from_name(&name).ok_or_else(|| {
+                    let names: Vec<_> = RandomizerKind::ALL.iter().map(|k| k.name()).collect();
+                    eyre!(
+                        "unknown randomizer {:?}, expected one of {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--gravity" => {
+                let name = iter
+                    .next()
+                    .ok_or_else(|| eyre!("--gravity needs a value"))?;
+                args.settings.gravity_curve = GravityCurve::from_name(&name).ok_or_else(|| {
+                    let names: Vec<_> = GravityCurve::ALL.iter().map(|c| c.name()).collect();
+                    eyre!(
+                        "unknown gravity curve {:?}, expected one of {}",
+                        name,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--lines-per-level" => match number(&mut iter, &arg)? {
+                0 => bail!("--lines-per-level can't be 0"),
+                lines => args.settings.lines_per_level = lines,
+            },
+            "--next" => {
+                let count = iter.next().ok_or_else(|| eyre!("--next needs a value"))?;
+                args.settings.next_queue = match count.parse() {
+                    Ok(count) if (1..=MAX_NEXT_QUEUE).contains(&count) => count,
+                    _ => bail!("--next should be 1 to {}, got {:?}", MAX_NEXT_QUEUE, count),
+                };
+            }
+            "--no-ghost" => args.settings.ghost = false,
+            "--das" => args.settings.das_ms = number(&mut iter, &arg)?,
+            "--arr" => args.settings.arr_ms = number(&mut iter, &arg)?,
+            "--sdf" => args.settings.soft_drop_factor = number(&mut iter, &arg)?,
+            "--seed" => args.seed = Some(number(&mut iter, &arg)?),
+            "--replay" => {
+                let path = iter.next().ok_or_else(|| eyre!("--replay needs a file"))?;
+                args.replay = Some(PathBuf::from(path));
+            }
+            _ => bail!("unknown argument {:?}", arg),
+        }
+    }
+    args.settings
+        .check()
+        .map_err(|what| eyre!("the {} setting is out of range", what))?;
+
+    Ok(args)
+}
+
+/// open the window and play, options come from the command line
+pub fn run() -> Result<()> {
+    let args = parse_args()?;
+
+    // init window, rendering runs at whatever the monitor does
+    let (mut rl, thread) = raylib::init()
+        .size(640, 480)
+        .title("Tetris")
+        .vsync()
+        .build();
+
+    // escape pauses rather than quitting
+    rl.set_exit_key(None);
+
+    let new_game = |settings| match args.seed {
+        Some(seed) => Game::with_seed(settings, seed),
+        None => Game::with_settings(settings),
+    };
+    let mut app = App::new(args.settings);
+    let mut game = new_game(*app.settings());
+    // the game left going last time, if there was one
+    let save_path = data_path(SAVE_FILE);
+    let mut saved = match save_path.as_deref().map(Game::load) {
+        Some(Ok(saved)) => {
+            app.offer_continue(*saved.settings());
+            Some(saved)
+        }
+        Some(Err(FileError::Io(error))) if error.kind() == io::ErrorKind::NotFound => None,
+        Some(Err(error)) => {
+            eprintln!("couldn't load the saved game: {}", error);
+            None
+        }
+        None => None,
+    };
+    // a message for the results screen, like where a replay went
+    let mut notice = None;
+
+    // the replay being watched, and any piece number being typed in to jump to
+    let mut playback = match &args.replay {
+        Some(path) => {
+            app.watch();
+            Some(Playback::new(Replay::load(path)?))
+        }
+        None => None,
+    };
+    let mut seek_entry = String::new();
+
+    // no path means there's nowhere to keep it, or the file there shouldn't
+    // be touched
+    let mut leaderboard_path = data_path(LEADERBOARD_FILE);
+    let mut leaderboard = match leaderboard_path.as_deref().map(load_leaderboard) {
+        Some(Some(leaderboard)) => leaderboard,
+        _ => {
+            leaderboard_path = None;
+            Leaderboard::new()
+        }
+    };
+
+    // the game itself ticks at a fixed rate, however many frames that is
+    let mut timestep = FixedTimestep::new();
+    // buttons seen since the last tick
+    let mut input = InputFrame::default();
+
+    // main loop
+    'main: while !rl.window_should_close() {
+        let keys = if app.screen() == &Screen::NameEntry {
+            read_name_keys(&mut rl, &mut app)
+        } else {
+            read_menu_keys(&rl)
+        };
+        for key in keys {
+            match app.press(key) {
+                Some(Command::NewGame) => {
+                    game = new_game(*app.settings());
+                    notice = None;
+                }
+                Some(Command::Continue) => {
+                    if let Some(saved) = saved.take() {
+                        game = saved;
+                        notice = None;
+                        // carrying on uses it up, it'll be saved again if the window's closed
+                        if let Some(path) = &save_path {
+                            if let Err(error) = fs::remove_file(path) {
+                                eprintln!("couldn't remove {}: {}", path.display(), error);
+                            }
+                        }
+                    }
+                }
+                Some(Command::SaveReplay) => {
+                    notice = Some(match save_replay(&game, Path::new(REPLAY_DIR)) {
+                        Ok(path) => format!("SAVED TO {}", path.display()),
+                        Err(error) => format!("COULDN'T SAVE REPLAY: {}", error),
+                    });
+                }
+                Some(Command::RecordScore) => {
+                    notice = Some(record_score(
+                        &game,
+                        app.name(),
+                        &mut leaderboard,
+                        leaderboard_path.as_deref(),
+                    ));
+                }
+                Some(Command::WatchReplay) => {
+                    playback = Some(Playback::new(game.replay()));
+                    seek_entry.clear();
+                }
+                Some(Command::Quit) => break 'main,
+                None => {}
+            }
+        }
+
+        if app.is_playing() {
+            input = input.merge(read_input(&rl));
+            let ticks = timestep.advance(Duration::from_secs_f32(rl.get_frame_time()));
+            if ticks > 0 {
+                // only the first tick can see a press, it's held after that
+                for _ in 0..ticks {
+                    game.step(input);
+                }
+                input = InputFrame::default();
+            }
+            if game.is_game_over() {
+                let high_score = Entry::new(&game, String::new(), unix_time())
+                    .and_then(|entry| leaderboard.place(&entry))
+                    .is_some();
+                if high_score {
+                    app.new_high_score();
+                } else {
+                    app.game_over();
+                }
+            }
+        } else if let (Screen::Replay, Some(playback)) = (app.screen(), &mut playback) {
+            control_playback(&mut rl, playback, &mut seek_entry);
+            let ticks = timestep.advance(Duration::from_secs_f32(rl.get_frame_time()));
+            playback.advance(ticks);
+        } else {
+            // don't let time spent off the board turn into a burst of ticks
+            timestep = FixedTimestep::new();
+            input = InputFrame::default();
+        }
+
+        draw(
+            &app,
+            &game,
+            &leaderboard,
+            playback
+                .as_ref()
+                .map(|playback| (playback, seek_entry.as_str())),
+            notice.as_deref(),
+            &mut rl,
+            &thread,
+        );
+    }
+
+    // closing the window mid game keeps it for next time
+    if let (true, Some(path)) = (app.in_game(), &save_path) {
+        game.save(path)
+            .wrap_err_with(|| format!("couldn't save the game to {}", path.display()))?;
+    }
+
+    Ok(())
+}
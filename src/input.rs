@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// state of every game button for a single frame
+/// a field is true while the button is held down,
+/// the game works out which buttons were just pressed by itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub left: bool,
+    pub right: bool,
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub rotate_180: bool,
+    pub soft_drop: bool,
+    pub hard_drop: bool,
+    pub hold: bool,
+}
+
+impl InputFrame {
+    /// buttons that are down this frame but weren't down last frame
+    pub fn pressed_since(self, last: InputFrame) -> InputFrame {
+        InputFrame {
+            left: self.left && !last.left,
+            right: self.right && !last.right,
+            rotate_cw: self.rotate_cw && !last.rotate_cw,
+            rotate_ccw: self.rotate_ccw && !last.rotate_ccw,
+            rotate_180: self.rotate_180 && !last.rotate_180,
+            soft_drop: self.soft_drop && !last.soft_drop,
+            hard_drop: self.hard_drop && !last.hard_drop,
+            hold: self.hold && !last.hold,
+        }
+    }
+
+    /// buttons down in either frame, for when a tick covers several frames
+    /// so a quick tap between ticks isn't missed
+    pub fn merge(self, other: InputFrame) -> InputFrame {
+        InputFrame {
+            left: self.left || other.left,
+            right: self.right || other.right,
+            rotate_cw: self.rotate_cw || other.rotate_cw,
+            rotate_ccw: self.rotate_ccw || other.rotate_ccw,
+            rotate_180: self.rotate_180 || other.rotate_180,
+            soft_drop: self.soft_drop || other.soft_drop,
+            hard_drop: self.hard_drop || other.hard_drop,
+            hold: self.hold || other.hold,
+        }
+    }
+    /// packed into a byte, one bit per button, for storing in replays
+    pub fn to_bits(self) -> u8 {
+        [
+            self.left,
+            self.right,
+            self.rotate_cw,
+            self.rotate_ccw,
+            self.rotate_180,
+            self.soft_drop,
+            self.hard_drop,
+            self.hold,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, &down)| bits | (u8::from(down) << i))
+    }
+
+    /// the other way round from `to_bits`
+    pub fn from_bits(bits: u8) -> InputFrame {
+        let down = |i: u8| bits & (1 << i) != 0;
+        InputFrame {
+            left: down(0),
+            right: down(1),
+            rotate_cw: down(2),
+            rotate_ccw: down(3),
+            rotate_180: down(4),
+            soft_drop: down(5),
+            hard_drop: down(6),
+            hold: down(7),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_round_trip() {
+        for bits in 0..=u8::MAX {
+            assert_eq!(InputFrame::from_bits(bits).to_bits(), bits);
+        }
+        let hold = InputFrame {
+            hold: true,
+            ..InputFrame::default()
+        };
+        assert_eq!(hold.to_bits(), 0b1000_0000);
+    }
+}
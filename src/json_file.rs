@@ -0,0 +1,148 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Serialize};
+
+// the saved game and the leaderboard are both json so they're easy to poke
+// at, as `{"version": n, "<field>": ...}`
+
+/// why a saved game or the leaderboard couldn't be read or written
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    /// written by a different version of the game
+    UnsupportedVersion(u32),
+    /// not json, or not what it should be
+    Corrupt(serde_json::Error),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(error) => write!(f, "{}", error),
+            FileError::UnsupportedVersion(version) => write!(
+                f,
+                "it's format version {}, which this build doesn't read",
+                version
+            ),
+            FileError::Corrupt(error) => write!(f, "it's corrupt: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileError::Io(error) => Some(error),
+            FileError::Corrupt(error) => Some(error),
+            FileError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for FileError {
+    fn from(error: io::Error) -> Self {
+        FileError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for FileError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            FileError::Io(error.into())
+        } else {
+            FileError::Corrupt(error)
+        }
+    }
+}
+
+impl FileError {
+    /// something that parsed fine but doesn't make sense
+    pub(crate) fn corrupt(what: &str) -> Self {
+        FileError::Corrupt(serde_json::Error::custom(format!("bad {}", what)))
+    }
+}
+
+pub(crate) fn write_versioned(
+    writer: impl Write,
+    version: u32,
+    field: &str,
+    value: &impl Serialize,
+) -> Result<(), FileError> {
+    let mut file = serde_json::Map::new();
+    file.insert("version".to_string(), version.into());
+    file.insert(field.to_string(), serde_json::to_value(value)?);
+    Ok(serde_json::to_writer(writer, &file)?)
+}
+
+// the version's read on its own first, a file from another version
+// probably won't have the fields this one expects
+pub(crate) fn read_versioned<T: DeserializeOwned>(
+    reader: impl Read,
+    version: u32,
+    field: &str,
+) -> Result<T, FileError> {
+    let mut file: serde_json::Map<String, serde_json::Value> = serde_json::from_reader(reader)?;
+    let missing = |field| serde_json::Error::custom(format!("missing field `{}`", field));
+    let found = u32::deserialize(file.get("version").ok_or_else(|| missing("version"))?)?;
+    if found != version {
+        return Err(FileError::UnsupportedVersion(found));
+    }
+    let value = file.remove(field).ok_or_else(|| missing(field))?;
+    Ok(T::deserialize(value)?)
+}
+
+/// write the whole thing next to the old file and then swap it in,
+/// so a crash partway through leaves the old one rather than half of each
+pub(crate) fn save_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), FileError>,
+) -> Result<(), FileError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&temp)?);
+    write(&mut writer)?;
+    let file = writer.into_inner().map_err(|error| error.into_error())?;
+    file.sync_all()?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+
+    #[test]
+    fn saving_swaps_the_whole_file_in() {
+        let dir = std::env::temp_dir().join(format!("raytris-json-file-{}", std::process::id()));
+        let path = dir.join("numbers.json");
+        let save = |numbers: &[u32]| {
+            save_atomically(&path, |writer| {
+                write_versioned(writer, 1, "numbers", &numbers)
+            })
+        };
+        let load = || {
+            let reader = BufReader::new(File::open(&path).unwrap());
+            read_versioned::<Vec<u32>>(reader, 1, "numbers").unwrap()
+        };
+
+        save(&[1, 2]).unwrap();
+        save(&[3]).unwrap();
+        assert_eq!(load(), [3]);
+        // nothing left lying around from the swap
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // and one that fails partway through leaves the last one as it was
+        let failed = save_atomically(&path, |_| Err(io::Error::other("full").into()));
+        assert!(matches!(failed, Err(FileError::Io(_))));
+        assert_eq!(load(), [3]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file::{read_versioned, save_atomically, write_versioned, FileError};
+use crate::mode::ticks_to_duration;
+use crate::{Finish, Game, GameMode};
+
+/// how many entries each mode keeps
+pub const LEADERBOARD_SIZE: usize = 10;
+/// longest name that can be typed in
+pub const MAX_NAME_LENGTH: usize = 12;
+/// bumped whenever the file layout changes
+pub const LEADERBOARD_VERSION: u32 = 1;
+
+/// one finished game on the leaderboard
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub mode: GameMode,
+    pub score: u32,
+    pub lines: u16,
+    pub level: u16,
+    /// how long the game took, in ticks
+    pub ticks: u32,
+    /// when it was played, in seconds since the unix epoch
+    pub date: u64,
+    /// where its replay was saved, if it was
+    pub replay: Option<PathBuf>,
+}
+
+impl Entry {
+    /// a finished game's result, if it's one that can go on the leaderboard,
+    /// sprints only count if all 40 lines got cleared
+    pub fn new(game: &Game, name: String, date: u64) -> Option<Entry> {
+        let mode = game.settings().mode;
+        match game.finish()? {
+            Finish::ToppedOut if mode == GameMode::Sprint => return None,
+            _ => {}
+        }
+        Some(Entry {
+            name,
+            mode,
+            score: game.score(),
+            lines: game.lines(),
+            level: game.level(),
+            ticks: game.ticks(),
+            date,
+            replay: None,
+        })
+    }
+
+    pub fn time(&self) -> Duration {
+        ticks_to_duration(self.ticks)
+    }
+}
+
+/// the best few games of each mode, sprints go by time and everything
+/// else by score
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    // best first within each mode, modes in `GameMode::ALL` order
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a mode's entries, best first
+    pub fn top(&self, mode: GameMode) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(move |entry| entry.mode == mode)
+    }
+
+    /// where an entry would go, from 0 for the top, if it'd make it on at all
+    pub fn place(&self, entry: &Entry) -> Option<usize> {
+        // ties go to whoever got there first
+        let place = self
+            .top(entry.mode)
+            .take_while(|other| ranking(other, entry) != Ordering::Greater)
+            .count();
+        Some(place).filter(|&place| place < LEADERBOARD_SIZE)
+    }
+
+    /// put an entry on the board, knocking off the last one if it's full,
+    /// and say where it went
+    pub fn add(&mut self, entry: Entry) -> Option<usize> {
+        let place = self.place(&entry)?;
+        self.entries.push(entry);
+        self.tidy();
+        Some(place)
+    }
+
+    // sort every mode best first and cut each down to size,
+    // the sort's stable so newer entries stay under older ones they tie with
+    fn tidy(&mut self) {
+        let mode_index = |entry: &Entry| GameMode::ALL.iter().position(|&mode| mode == entry.mode);
+        self.entries
+            .sort_by(|a, b| mode_index(a).cmp(&mode_index(b)).then(ranking(a, b)));
+        let mut counts = [0; GameMode::ALL.len()];
+        self.entries.retain(|entry| {
+            let count = &mut counts[mode_index(entry).unwrap_or(0)];
+            *count += 1;
+            *count <= LEADERBOARD_SIZE
+        });
+    }
+
+    pub fn write_to(&self, writer: impl Write) -> Result<(), FileError> {
+        write_versioned(writer, LEADERBOARD_VERSION, "entries", &self.entries)
+    }
+
+    pub fn read_from(reader: impl Read) -> Result<Leaderboard, FileError> {
+        let mut leaderboard = Leaderboard {
+            entries: read_versioned(reader, LEADERBOARD_VERSION, "entries")?,
+        };
+        // in case it's been edited by hand
+        leaderboard.tidy();
+        Ok(leaderboard)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FileError> {
+        save_atomically(path.as_ref(), |writer| self.write_to(writer))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Leaderboard, FileError> {
+        Leaderboard::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+// better entries sort first
+fn ranking(a: &Entry, b: &Entry) -> Ordering {
+    match a.mode {
+        GameMode::Sprint => a.ticks.cmp(&b.ticks),
+        _ => b.score.cmp(&a.score).then(a.ticks.cmp(&b.ticks)),
+    }
+}
+
+/// seconds since the unix epoch as a utc "yyyy-mm-dd"
+pub fn format_date(seconds: u64) -> String {
+    // days to a civil date, from howard hinnant's date algorithms
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mode: GameMode, score: u32, ticks: u32, name: &str) -> Entry {
+        Entry {
+            name: name.to_string(),
+            mode,
+            score,
+            lines: 0,
+            level: 1,
+            ticks,
+            date: 0,
+            replay: None,
+        }
+    }
+
+    #[test]
+    fn keeps_the_best_ten_of_each_mode() {
+        let mut leaderboard = Leaderboard::new();
+        for n in 0..15 {
+            leaderboard.add(entry(GameMode::Marathon, n * 100, 1000, "M"));
+            leaderboard.add(entry(GameMode::Sprint, 0, 3000 + n, "S"));
+        }
+        let scores: Vec<_> = leaderboard
+            .top(GameMode::Marathon)
+            .map(|entry| entry.score)
+            .collect();
+        assert_eq!(
+            scores,
+            [1400, 1300, 1200, 1100, 1000, 900, 800, 700, 600, 500]
+        );
+        let times: Vec<_> = leaderboard
+            .top(GameMode::Sprint)
+            .map(|entry| entry.ticks)
+            .collect();
+        assert_eq!(times, (3000..3010).collect::<Vec<_>>());
+        assert_eq!(leaderboard.top(GameMode::Ultra).count(), 0);
+
+        // too slow to make it, and a tie goes under the one already there
+        assert_eq!(leaderboard.add(entry(GameMode::Sprint, 0, 3010, "S")), None);
+        assert_eq!(
+            leaderboard.add(entry(GameMode::Marathon, 900, 1000, "NEW")),
+            Some(6)
+        );
+        let names: Vec<_> = leaderboard
+            .top(GameMode::Marathon)
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names[5..8], ["M", "NEW", "M"]);
+    }
+
+    #[test]
+    fn only_finished_games_and_cleared_sprints_count() {
+        use crate::{InputFrame, Settings};
+
+        let sprint = Settings {
+            mode: GameMode::Sprint,
+            ..Settings::default()
+        };
+        let mut game = Game::with_seed(sprint, 3);
+        assert_eq!(Entry::new(&game, String::new(), 0), None);
+        while !game.is_game_over() {
+            game.step(InputFrame {
+                hard_drop: true,
+                ..InputFrame::default()
+            });
+            game.step(InputFrame::default());
+        }
+        assert_eq!(game.finish(), Some(Finish::ToppedOut));
+        assert_eq!(Entry::new(&game, String::new(), 0), None);
+
+        let mut game = Game::with_seed(Settings::default(), 3);
+        while !game.is_game_over() {
+            game.step(InputFrame {
+                hard_drop: true,
+                ..InputFrame::default()
+            });
+            game.step(InputFrame::default());
+        }
+        let entry = Entry::new(&game, "ME".to_string(), 1).unwrap();
+        assert_eq!(entry.score, game.score());
+        assert_eq!(entry.ticks, game.ticks());
+    }
+
+    #[test]
+    fn round_trips_and_refuses_junk() {
+        let mut leaderboard = Leaderboard::new();
+        let mut best = entry(GameMode::Ultra, 5000, 7200, "ACE");
+        best.replay = Some(PathBuf::from("replays/ultra-1-2.rtr"));
+        leaderboard.add(best);
+        leaderboard.add(entry(GameMode::Endless, 100, 50, "B"));
+
+        let mut bytes = Vec::new();
+        leaderboard.write_to(&mut bytes).unwrap();
+        assert_eq!(
+            Leaderboard::read_from(bytes.as_slice()).unwrap(),
+            leaderboard
+        );
+        assert!(matches!(
+            Leaderboard::read_from(&bytes[..bytes.len() / 2]),
+            Err(FileError::Corrupt(_))
+        ));
+        assert!(matches!(
+            Leaderboard::read_from(&b"{\"version\":9,\"entries\":[]}"[..]),
+            Err(FileError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_281_600), "2026-10-18");
+    }
+}
@@ -0,0 +1,43 @@
+//! headless tetris core
+//! holds the board, pieces and rules, knows nothing about windows or rendering
+//! drive it by calling `Game::step` once per tick with an `InputFrame`
+
+mod color;
+mod game;
+mod gravity;
+mod input;
+mod json_file;
+mod leaderboard;
+mod menu;
+mod mode;
+mod playback;
+mod randomizer;
+mod replay;
+mod save;
+mod scoring;
+mod screen;
+mod settings;
+#[cfg(test)]
+mod test_input;
+mod tetromino;
+mod timestep;
+
+pub use color::Color;
+pub use game::{Game, GridSquare, GRID_HORIZONTAL_SIZE, GRID_VERTICAL_SIZE, TICKS_PER_SECOND};
+pub use gravity::{GravityCurve, GRAVITY_ONE};
+pub use input::InputFrame;
+pub use json_file::FileError;
+pub use leaderboard::{
+    format_date, Entry, Leaderboard, LEADERBOARD_SIZE, LEADERBOARD_VERSION, MAX_NAME_LENGTH,
+};
+pub use menu::{Menu, PauseItem, ResultsItem, TitleItem};
+pub use mode::{format_time, Finish, GameMode};
+pub use playback::Playback;
+pub use randomizer::{AnyRandomizer, Nes, Randomizer, RandomizerKind, SevenBag, Tgm, Uniform};
+pub use replay::{Replay, ReplayError, Summary, REPLAY_VERSION};
+pub use save::SAVE_VERSION;
+pub use scoring::{Clear, Scoring, Spin};
+pub use screen::{App, Command, MenuKey, Screen};
+pub use settings::{Settings, SettingsItem, MAX_NEXT_QUEUE};
+pub use tetromino::{Rotation, Tetromino};
+pub use timestep::FixedTimestep;
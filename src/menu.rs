@@ -0,0 +1,144 @@
+/// a list of choices with one of them picked out,
+/// moved through with up and down and wrapping round at the ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Menu<T: 'static> {
+    items: &'static [T],
+    selected: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    /// starts on the first item, there has to be at least one
+    pub fn new(items: &'static [T]) -> Self {
+        assert!(!items.is_empty(), "menu with nothing in it");
+        Menu { items, selected: 0 }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+
+    pub fn selected(&self) -> T {
+        self.items[self.selected]
+    }
+
+    /// every item, and whether it's the selected one
+    pub fn items(&self) -> impl Iterator<Item = (T, bool)> + '_ {
+        self.items
+            .iter()
+            .enumerate()
+            .map(move |(i, &item)| (item, i == self.selected))
+    }
+}
+
+/// the choices on the pause menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    Restart,
+    Settings,
+    QuitToTitle,
+}
+
+impl PauseItem {
+    pub const ALL: [PauseItem; 4] = [
+        PauseItem::Resume,
+        PauseItem::Restart,
+        PauseItem::Settings,
+        PauseItem::QuitToTitle,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Restart => "RESTART",
+            PauseItem::Settings => "SETTINGS",
+            PauseItem::QuitToTitle => "QUIT TO TITLE",
+        }
+    }
+}
+
+/// the choices on the title screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleItem {
+    /// only there when there's a saved game to carry on with
+    Continue,
+    Play,
+    HighScores,
+    Settings,
+    Quit,
+}
+
+impl TitleItem {
+    pub const ALL: [TitleItem; 4] = [
+        TitleItem::Play,
+        TitleItem::HighScores,
+        TitleItem::Settings,
+        TitleItem::Quit,
+    ];
+    pub const WITH_CONTINUE: [TitleItem; 5] = [
+        TitleItem::Continue,
+        TitleItem::Play,
+        TitleItem::HighScores,
+        TitleItem::Settings,
+        TitleItem::Quit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TitleItem::Continue => "CONTINUE",
+            TitleItem::Play => "PLAY",
+            TitleItem::HighScores => "HIGH SCORES",
+            TitleItem::Settings => "SETTINGS",
+            TitleItem::Quit => "QUIT",
+        }
+    }
+}
+
+/// the choices once a game's finished
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultsItem {
+    PlayAgain,
+    WatchReplay,
+    SaveReplay,
+    QuitToTitle,
+}
+
+impl ResultsItem {
+    pub const ALL: [ResultsItem; 4] = [
+        ResultsItem::PlayAgain,
+        ResultsItem::WatchReplay,
+        ResultsItem::SaveReplay,
+        ResultsItem::QuitToTitle,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResultsItem::PlayAgain => "PLAY AGAIN",
+            ResultsItem::WatchReplay => "WATCH REPLAY",
+            ResultsItem::SaveReplay => "SAVE REPLAY",
+            ResultsItem::QuitToTitle => "QUIT TO TITLE",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_wraps_round() {
+        let mut menu = Menu::new(&PauseItem::ALL);
+        assert_eq!(menu.selected(), PauseItem::Resume);
+        menu.up();
+        assert_eq!(menu.selected(), PauseItem::QuitToTitle);
+        menu.down();
+        menu.down();
+        assert_eq!(menu.selected(), PauseItem::Restart);
+        let selected: Vec<_> = menu.items().filter(|&(_, selected)| selected).collect();
+        assert_eq!(selected, [(PauseItem::Restart, true)]);
+    }
+}
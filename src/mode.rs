@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::TICKS_PER_SECOND;
+
+/// the kind of game being played, picked on the mode select screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// level up all the way to 150 lines
+    #[default]
+    Marathon,
+    /// marathon with no line goal, keep going until topping out
+    Endless,
+    /// clear 40 lines as fast as possible
+    Sprint,
+    /// as many points as possible in two minutes
+    Ultra,
+}
+
+/// how a game came to an end
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Finish {
+    /// reached the mode's line goal
+    Cleared,
+    /// ran out the mode's clock
+    TimeUp,
+    /// the stack reached the top, a loss in every mode
+    ToppedOut,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Marathon,
+        GameMode::Endless,
+        GameMode::Sprint,
+        GameMode::Ultra,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "MARATHON",
+            GameMode::Endless => "ENDLESS",
+            GameMode::Sprint => "SPRINT",
+            GameMode::Ultra => "ULTRA",
+        }
+    }
+
+    /// short name used in replay file names and `raytris verify`'s output
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "marathon",
+            GameMode::Endless => "endless",
+            GameMode::Sprint => "sprint",
+            GameMode::Ultra => "ultra",
+        }
+    }
+
+    /// lines to clear to win, if there's a goal at all
+    pub fn line_goal(self) -> Option<u16> {
+        match self {
+            GameMode::Marathon => Some(150),
+            GameMode::Sprint => Some(40),
+            GameMode::Endless | GameMode::Ultra => None,
+        }
+    }
+
+    /// ticks before the game stops, if it's against the clock
+    pub fn time_limit(self) -> Option<u32> {
+        match self {
+            GameMode::Ultra => Some(2 * 60 * TICKS_PER_SECOND),
+            _ => None,
+        }
+    }
+
+    /// whether clearing lines speeds things up, the timed modes stay at level 1
+    /// so it's only the player getting faster
+    pub fn levels_up(self) -> bool {
+        matches!(self, GameMode::Marathon | GameMode::Endless)
+    }
+}
+
+/// a number of ticks as time on the clock
+pub fn ticks_to_duration(ticks: u32) -> Duration {
+    Duration::from_nanos(u64::from(ticks) * 1_000_000_000 / u64::from(TICKS_PER_SECOND))
+}
+
+/// minutes, seconds and milliseconds, like 1:05.250
+pub fn format_time(time: Duration) -> String {
+    let millis = time.as_millis();
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_to_the_millisecond() {
+        assert_eq!(format_time(ticks_to_duration(0)), "0:00.000");
+        assert_eq!(format_time(ticks_to_duration(1)), "0:00.016");
+        assert_eq!(format_time(ticks_to_duration(3903)), "1:05.050");
+        let ultra = GameMode::Ultra.time_limit().unwrap();
+        assert_eq!(format_time(ticks_to_duration(ultra)), "2:00.000");
+    }
+}
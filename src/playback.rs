@@ -0,0 +1,235 @@
+use crate::{Game, InputFrame, Replay};
+
+// playback speeds in quarters, so 0.25x up to 8x
+const SPEEDS: [u32; 6] = [1, 2, 4, 8, 16, 32];
+const NORMAL_SPEED: usize = 2;
+
+/// plays a replay back through the same game logic it was recorded with,
+/// at any speed, paused, a tick at a time or jumping about by piece
+///
+/// jumping backwards re-simulates from the start, which is quick enough
+/// even for long games since nothing gets drawn on the way
+pub struct Playback {
+    replay: Replay,
+    game: Game,
+    // how many of the replay's inputs the game's been fed
+    tick: usize,
+    // the tick each piece comes in on, piece 1 coming in on tick 0
+    piece_starts: Vec<usize>,
+    paused: bool,
+    speed: usize,
+    // quarter ticks owed at the current speed
+    quarters: u32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        // play it through once to find where each piece starts
+        let mut game = replay.new_game();
+        let mut piece_starts = vec![0];
+        for (tick, input) in replay.frames().enumerate() {
+            game.step(input);
+            if game.pieces() as usize == piece_starts.len() {
+                piece_starts.push(tick + 1);
+            }
+        }
+        // no piece comes in after the last one's locked
+        if piece_starts.len() > 1 && game.is_game_over() {
+            piece_starts.pop();
+        }
+
+        Playback {
+            game: replay.new_game(),
+            replay,
+            tick: 0,
+            piece_starts,
+            paused: false,
+            speed: NORMAL_SPEED,
+            quarters: 0,
+        }
+    }
+
+    /// the game as it is at the current tick
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// carry on by some number of ticks at normal speed,
+    /// which is more or fewer at other speeds and none at all while paused
+    pub fn advance(&mut self, ticks: u32) {
+        if self.paused {
+            return;
+        }
+        self.quarters += ticks * SPEEDS[self.speed];
+        for _ in 0..self.quarters / 4 {
+            self.step();
+        }
+        self.quarters %= 4;
+    }
+
+    /// pause and go forward a single tick
+    pub fn step_frame(&mut self) {
+        self.paused = true;
+        self.step();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// how many times normal speed it's playing at
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed] as f32 / 4.0
+    }
+
+    /// inputs played so far
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// total inputs in the replay
+    pub fn len(&self) -> usize {
+        self.replay.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.inputs.is_empty()
+    }
+
+    /// played every input there is
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.len()
+    }
+
+    /// which piece is being played, counting from 1
+    pub fn piece(&self) -> usize {
+        self.piece_starts
+            .partition_point(|&start| start <= self.tick)
+            .max(1)
+    }
+
+    /// how many pieces there are in the whole replay
+    pub fn piece_count(&self) -> usize {
+        self.piece_starts.len()
+    }
+
+    /// jump to the tick a piece comes in on, counting pieces from 1
+    pub fn seek_piece(&mut self, piece: usize) {
+        let piece = piece.clamp(1, self.piece_count());
+        self.seek_tick(self.piece_starts[piece - 1]);
+    }
+
+    /// jump to just after some number of inputs have been played
+    pub fn seek_tick(&mut self, tick: usize) {
+        let tick = tick.min(self.len());
+        if tick < self.tick {
+            self.game = self.replay.new_game();
+            self.tick = 0;
+        }
+        while self.tick < tick {
+            self.step();
+        }
+        self.quarters = 0;
+    }
+
+    fn step(&mut self) {
+        if let Some(&bits) = self.replay.inputs.get(self.tick) {
+            self.game.step(InputFrame::from_bits(bits));
+            self.tick += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_input::{drops, played};
+    use crate::Settings;
+
+    fn replay(ticks: usize) -> Replay {
+        played(Settings::default(), 42, drops().take(ticks)).replay()
+    }
+
+    #[test]
+    fn speeds_from_a_quarter_to_eight_times() {
+        let mut playback = Playback::new(replay(1000));
+        playback.advance(10);
+        assert_eq!(playback.tick(), 10);
+
+        playback.slower();
+        playback.slower();
+        playback.slower();
+        assert_eq!(playback.speed(), 0.25);
+        playback.advance(3);
+        assert_eq!(playback.tick(), 10);
+        playback.advance(1);
+        assert_eq!(playback.tick(), 11);
+
+        for _ in 0..10 {
+            playback.faster();
+        }
+        assert_eq!(playback.speed(), 8.0);
+        playback.advance(2);
+        assert_eq!(playback.tick(), 27);
+    }
+
+    #[test]
+    fn pausing_and_stepping() {
+        let mut playback = Playback::new(replay(100));
+        playback.toggle_pause();
+        playback.advance(10);
+        assert_eq!(playback.tick(), 0);
+        playback.step_frame();
+        playback.step_frame();
+        assert_eq!(playback.tick(), 2);
+        assert!(playback.is_paused());
+
+        // and it stops at the end
+        playback.toggle_pause();
+        playback.advance(1000);
+        assert!(playback.is_finished());
+        assert_eq!(playback.tick(), 100);
+    }
+
+    #[test]
+    fn seeking_ends_up_where_playing_would() {
+        let replay = replay(2000);
+        let mut playback = Playback::new(replay.clone());
+        assert!(playback.piece_count() > 10);
+
+        playback.seek_piece(10);
+        assert_eq!(playback.piece(), 10);
+        assert_eq!(playback.game().pieces(), 9);
+        let tick = playback.tick();
+
+        // back to the start and forward again
+        playback.seek_piece(3);
+        assert_eq!(playback.piece(), 3);
+        assert_eq!(playback.game().pieces(), 2);
+
+        let mut straight = replay.new_game();
+        for input in replay.frames().take(tick) {
+            straight.step(input);
+        }
+        playback.seek_tick(tick);
+        assert_eq!(playback.game().grid(), straight.grid());
+        assert_eq!(playback.game().score(), straight.score());
+        assert_eq!(playback.game().piece_cells(), straight.piece_cells());
+    }
+}
@@ -0,0 +1,261 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::Tetromino;
+
+/// picks the order pieces come out in
+pub trait Randomizer {
+    /// the next piece in the sequence
+    fn next(&mut self) -> Tetromino;
+}
+
+/// which randomizer a game uses, picked at startup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RandomizerKind {
+    #[default]
+    SevenBag,
+    Uniform,
+    Nes,
+    Tgm,
+}
+
+impl RandomizerKind {
+    pub const ALL: [RandomizerKind; 4] = [
+        RandomizerKind::SevenBag,
+        RandomizerKind::Uniform,
+        RandomizerKind::Nes,
+        RandomizerKind::Tgm,
+    ];
+
+    /// short name used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomizerKind::SevenBag => "bag",
+            RandomizerKind::Uniform => "uniform",
+            RandomizerKind::Nes => "nes",
+            RandomizerKind::Tgm => "tgm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RandomizerKind> {
+        RandomizerKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+
+    pub fn create(self, seed: u64) -> AnyRandomizer {
+        match self {
+            RandomizerKind::SevenBag => AnyRandomizer::SevenBag(SevenBag::new(seed)),
+            RandomizerKind::Uniform => AnyRandomizer::Uniform(Uniform::new(seed)),
+            RandomizerKind::Nes => AnyRandomizer::Nes(Nes::new(seed)),
+            RandomizerKind::Tgm => AnyRandomizer::Tgm(Tgm::new(seed)),
+        }
+    }
+}
+
+/// whichever randomizer a game picked, as an enum rather than a trait object
+/// so it can be saved along with the rest of the game, rng state and all
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AnyRandomizer {
+    SevenBag(SevenBag),
+    Uniform(Uniform),
+    Nes(Nes),
+    Tgm(Tgm),
+}
+
+impl Randomizer for AnyRandomizer {
+    fn next(&mut self) -> Tetromino {
+        match self {
+            AnyRandomizer::SevenBag(randomizer) => randomizer.next(),
+            AnyRandomizer::Uniform(randomizer) => randomizer.next(),
+            AnyRandomizer::Nes(randomizer) => randomizer.next(),
+            AnyRandomizer::Tgm(randomizer) => randomizer.next(),
+        }
+    }
+}
+
+/// any piece at any time, so droughts can go on forever
+/// (the original behaviour)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Uniform {
+    rng: ChaCha8Rng,
+}
+
+impl Uniform {
+    pub fn new(seed: u64) -> Self {
+        Uniform {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Randomizer for Uniform {
+    fn next(&mut self) -> Tetromino {
+        random_piece(&mut self.rng)
+    }
+}
+
+/// deals out all seven pieces in a shuffled order before starting again
+/// (guideline behaviour)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SevenBag {
+    rng: ChaCha8Rng,
+    bag: Vec<Tetromino>,
+}
+
+impl SevenBag {
+    pub fn new(seed: u64) -> Self {
+        SevenBag {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            bag: Vec::with_capacity(Tetromino::ALL.len()),
+        }
+    }
+}
+
+impl Randomizer for SevenBag {
+    fn next(&mut self) -> Tetromino {
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&Tetromino::ALL);
+            self.bag.shuffle(&mut self.rng);
+        }
+        // can't be empty, just refilled it
+        self.bag.pop().unwrap()
+    }
+}
+
+/// rolls an eighth "reroll" option too, and rerolls once if it gets that
+/// or the same piece as last time
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Nes {
+    rng: ChaCha8Rng,
+    last: Option<Tetromino>,
+}
+
+impl Nes {
+    pub fn new(seed: u64) -> Self {
+        Nes {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            last: None,
+        }
+    }
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self) -> Tetromino {
+        let roll = self.rng.gen_range(0..Tetromino::ALL.len() as u32 + 1) as usize;
+        let piece = match Tetromino::ALL.get(roll) {
+            Some(&piece) if Some(piece) != self.last => piece,
+            _ => random_piece(&mut self.rng),
+        };
+        self.last = Some(piece);
+        piece
+    }
+}
+
+// how many recent pieces the tgm randomizer tries to avoid,
+// and how many times it tries before giving up
+const TGM_HISTORY: usize = 4;
+const TGM_ROLLS: usize = 4;
+const TGM_FIRST: [Tetromino; 4] = [Tetromino::I, Tetromino::J, Tetromino::L, Tetromino::T];
+
+/// keeps the last few pieces and rerolls a few times to avoid them
+/// (tgm1 style, which never starts on an S, Z or O)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tgm {
+    rng: ChaCha8Rng,
+    history: [Tetromino; TGM_HISTORY],
+    first: bool,
+}
+
+impl Tgm {
+    pub fn new(seed: u64) -> Self {
+        Tgm {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            history: [Tetromino::Z; TGM_HISTORY],
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self) -> Tetromino {
+        let piece = if self.first {
+            self.first = false;
+            TGM_FIRST[self.rng.gen_range(0..TGM_FIRST.len() as u32) as usize]
+        } else {
+            let mut piece = random_piece(&mut self.rng);
+            for _ in 1..TGM_ROLLS {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = random_piece(&mut self.rng);
+            }
+            piece
+        };
+
+        self.history.rotate_right(1);
+        self.history[0] = piece;
+        piece
+    }
+}
+
+/// any of the seven pieces
+/// sampled as a u32 so the sequence is the same on every platform
+fn random_piece(rng: &mut ChaCha8Rng) -> Tetromino {
+    Tetromino::ALL[rng.gen_range(0..Tetromino::ALL.len() as u32) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seven_bag_deals_every_piece_once_per_bag() {
+        let mut randomizer = SevenBag::new(7);
+        for _ in 0..10 {
+            let mut bag: Vec<_> = (0..7).map(|_| randomizer.next() as usize).collect();
+            bag.sort_unstable();
+            assert_eq!(bag, [0, 1, 2, 3, 4, 5, 6]);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        for kind in RandomizerKind::ALL {
+            let mut a = kind.create(1234);
+            let mut b = kind.create(1234);
+            for _ in 0..100 {
+                assert_eq!(a.next(), b.next(), "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn saved_randomizers_carry_on_where_they_left_off() {
+        for kind in RandomizerKind::ALL {
+            let mut randomizer = kind.create(99);
+            for _ in 0..10 {
+                randomizer.next();
+            }
+            let json = serde_json::to_string(&randomizer).unwrap();
+            let mut loaded: AnyRandomizer = serde_json::from_str(&json).unwrap();
+            for _ in 0..100 {
+                assert_eq!(loaded.next(), randomizer.next(), "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn tgm_never_starts_with_s_z_or_o() {
+        for seed in 0..50 {
+            let first = Tgm::new(seed).next();
+            assert!(
+                ![Tetromino::S, Tetromino::Z, Tetromino::O].contains(&first),
+                "seed {} started with {:?}",
+                seed,
+                first
+            );
+        }
+    }
+}
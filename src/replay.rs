@@ -0,0 +1,354 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::mode::ticks_to_duration;
+use crate::{Game, GameMode, GravityCurve, InputFrame, RandomizerKind, Settings};
+
+// first bytes of every replay file
+const MAGIC: &[u8; 4] = b"RTRP";
+/// bumped whenever the layout below changes
+pub const REPLAY_VERSION: u16 = 1;
+
+/// everything needed to play a game back exactly: the settings and seed it
+/// started with and the buttons held on every tick
+///
+/// on disk it's all little endian:
+/// magic, version (u16), seed (u64), then the settings (mode, randomizer and
+/// gravity curve as u8 indexes, lines per level u16, next queue u8, ghost u8,
+/// lock delay u32, lock resets u16, das u32, arr u32, soft drop factor u16),
+/// then the tick count (u32) and a byte of `InputFrame::to_bits` per tick,
+/// then a u8 saying whether there's a claimed summary, and if there is the score (u32), lines (u16), pieces (u32),
+/// ticks (u32) and board hash (u64)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub settings: Settings,
+    pub seed: u64,
+    /// one per tick, as `InputFrame::to_bits`
+    pub inputs: Vec<u8>,
+    /// how the recorded game ended up, if the file says, for checking
+    /// against playing it back
+    pub claimed: Option<Summary>,
+}
+
+/// the results of a game, enough to tell if a replay really plays out
+/// the way it's meant to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub score: u32,
+    pub lines: u16,
+    pub pieces: u32,
+    pub ticks: u32,
+    /// see `Game::board_hash`
+    pub board_hash: u64,
+}
+
+impl Summary {
+    pub fn of(game: &Game) -> Self {
+        Summary {
+            score: game.score(),
+            lines: game.lines(),
+            pieces: game.pieces(),
+            ticks: game.ticks(),
+            board_hash: game.board_hash(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        ticks_to_duration(self.ticks)
+    }
+
+    /// names of the fields that aren't the same in both
+    pub fn differences(&self, other: &Summary) -> Vec<&'static str> {
+        let fields = [
+            ("score", self.score != other.score),
+            ("lines", self.lines != other.lines),
+            ("pieces", self.pieces != other.pieces),
+            ("ticks", self.ticks != other.ticks),
+            ("board_hash", self.board_hash != other.board_hash),
+        ];
+        fields
+            .iter()
+            .filter(|(_, differs)| *differs)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+/// why a replay couldn't be read
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// doesn't start with the magic bytes
+    NotAReplay,
+    /// written by a different version of the game
+    UnsupportedVersion(u16),
+    /// a setting that doesn't exist or is out of range
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "couldn't read replay: {}", error),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay is format version {}, this build reads {}",
+                version, REPLAY_VERSION
+            ),
+            ReplayError::Corrupt(what) => write!(f, "replay has a bad {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+impl Replay {
+    /// the inputs as the game saw them
+    pub fn frames(&self) -> impl Iterator<Item = InputFrame> + '_ {
+        self.inputs.iter().map(|&bits| InputFrame::from_bits(bits))
+    }
+
+    /// a fresh game set up the way the recorded one was
+    pub fn new_game(&self) -> Game {
+        Game::with_seed(self.settings, self.seed)
+    }
+
+    /// run the whole thing through and return the game as it ended up
+    pub fn play(&self) -> Game {
+        let mut game = self.new_game();
+        for input in self.frames() {
+            game.step(input);
+        }
+        game
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let settings = &self.settings;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&REPLAY_VERSION.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&[
+            index_of(&GameMode::ALL, settings.mode),
+            index_of(&RandomizerKind::ALL, settings.randomizer),
+            index_of(&GravityCurve::ALL, settings.gravity_curve),
+        ])?;
+        writer.write_all(&settings.lines_per_level.to_le_bytes())?;
+        writer.write_all(&[settings.next_queue as u8, u8::from(settings.ghost)])?;
+        writer.write_all(&settings.lock_delay_ms.to_le_bytes())?;
+        writer.write_all(&settings.lock_resets.to_le_bytes())?;
+        writer.write_all(&settings.das_ms.to_le_bytes())?;
+        writer.write_all(&settings.arr_ms.to_le_bytes())?;
+        writer.write_all(&settings.soft_drop_factor.to_le_bytes())?;
+        writer.write_all(&(self.inputs.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.inputs)?;
+        match &self.claimed {
+            Some(claimed) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&claimed.score.to_le_bytes())?;
+                writer.write_all(&claimed.lines.to_le_bytes())?;
+                writer.write_all(&claimed.pieces.to_le_bytes())?;
+                writer.write_all(&claimed.ticks.to_le_bytes())?;
+                writer.write_all(&claimed.board_hash.to_le_bytes())
+            }
+            None => writer.write_all(&[0]),
+        }
+    }
+
+    pub fn read_from(mut reader: impl Read) -> Result<Replay, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = u16::from_le_bytes(read(&mut reader)?);
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(read(&mut reader)?);
+        let [mode, randomizer, gravity_curve] = read(&mut reader)?;
+        let lines_per_level = u16::from_le_bytes(read(&mut reader)?);
+        let [next_queue, ghost] = read(&mut reader)?;
+        let settings = Settings {
+            mode: *GameMode::ALL
+                .get(usize::from(mode))
+                .ok_or(ReplayError::Corrupt("mode"))?,
+            randomizer: *RandomizerKind::ALL
+                .get(usize::from(randomizer))
+                .ok_or(ReplayError::Corrupt("randomizer"))?,
+            gravity_curve: *GravityCurve::ALL
+                .get(usize::from(gravity_curve))
+                .ok_or(ReplayError::Corrupt("gravity curve"))?,
+            lines_per_level,
+            next_queue: usize::from(next_queue),
+            ghost: ghost != 0,
+            lock_delay_ms: u32::from_le_bytes(read(&mut reader)?),
+            lock_resets: u16::from_le_bytes(read(&mut reader)?),
+            das_ms: u32::from_le_bytes(read(&mut reader)?),
+            arr_ms: u32::from_le_bytes(read(&mut reader)?),
+            soft_drop_factor: u16::from_le_bytes(read(&mut reader)?),
+        };
+        // nothing the game couldn't have been played with
+        settings.check().map_err(ReplayError::Corrupt)?;
+
+        let ticks = u32::from_le_bytes(read(&mut reader)?) as usize;
+        let mut inputs = Vec::new();
+        reader
+            .by_ref()
+            .take(ticks as u64)
+            .read_to_end(&mut inputs)?;
+        if inputs.len() != ticks {
+            return Err(ReplayError::Corrupt("input count"));
+        }
+
+        let claimed = match read(&mut reader)? {
+            [0] => None,
+            [1] => Some(Summary {
+                score: u32::from_le_bytes(read(&mut reader)?),
+                lines: u16::from_le_bytes(read(&mut reader)?),
+                pieces: u32::from_le_bytes(read(&mut reader)?),
+                ticks: u32::from_le_bytes(read(&mut reader)?),
+                board_hash: u64::from_le_bytes(read(&mut reader)?),
+            }),
+            _ => return Err(ReplayError::Corrupt("summary")),
+        };
+
+        Ok(Replay {
+            settings,
+            seed,
+            inputs,
+            claimed,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        Replay::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+/// a fixed number of bytes
+fn read<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// where a choice is in its list of every choice, for storing as a byte
+fn index_of<T: PartialEq>(all: &[T], item: T) -> u8 {
+    all.iter().position(|other| *other == item).unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_input::{mash, played};
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let settings = Settings {
+            mode: GameMode::Sprint,
+            randomizer: RandomizerKind::Tgm,
+            gravity_curve: GravityCurve::Nes,
+            das_ms: 100,
+            arr_ms: 0,
+            ghost: false,
+            ..Settings::default()
+        };
+        let replay = played(settings, 99, mash().take(1000)).replay();
+        let mut bytes = Vec::new();
+        replay.write_to(&mut bytes).unwrap();
+        assert_eq!(Replay::read_from(bytes.as_slice()).unwrap(), replay);
+    }
+
+    #[test]
+    fn playing_back_ends_up_the_same() {
+        let game = played(Settings::default(), 7, mash().take(5000));
+        let replayed = game.replay().play();
+        assert_eq!(replayed.grid(), game.grid());
+        assert_eq!(replayed.score(), game.score());
+        assert_eq!(replayed.lines(), game.lines());
+        assert_eq!(replayed.piece_cells(), game.piece_cells());
+    }
+
+    #[test]
+    fn catches_claims_that_dont_play_out() {
+        let game = played(Settings::default(), 11, mash().take(3000));
+        let replay = game.replay();
+        let claimed = replay.claimed.unwrap();
+        let actual = Summary::of(&replay.play());
+        assert_eq!(claimed.differences(&actual), Vec::<&str>::new());
+        assert_ne!(
+            claimed.board_hash,
+            Summary::of(&Game::with_seed(Settings::default(), 11)).board_hash
+        );
+
+        let mut tampered = claimed;
+        tampered.score += 100;
+        tampered.ticks -= 60;
+        assert_eq!(tampered.differences(&actual), vec!["score", "ticks"]);
+    }
+
+    #[test]
+    fn refuses_bad_files() {
+        let mut bytes = Vec::new();
+        played(Settings::default(), 1, mash().take(10))
+            .replay()
+            .write_to(&mut bytes)
+            .unwrap();
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(REPLAY_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Replay::read_from(newer.as_slice()),
+            Err(ReplayError::UnsupportedVersion(_))
+        ));
+
+        // handling far out of range, which would overflow turning it into ticks
+        let mut bad_das = bytes.clone();
+        bad_das[27..31].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Replay::read_from(bad_das.as_slice()),
+            Err(ReplayError::Corrupt("das"))
+        ));
+
+        let mut bad_mode = bytes.clone();
+        bad_mode[14] = 200;
+        assert!(matches!(
+            Replay::read_from(bad_mode.as_slice()),
+            Err(ReplayError::Corrupt("mode"))
+        ));
+
+        // cut off partway through the inputs, before the 23 bytes of summary
+        assert!(matches!(
+            Replay::read_from(&bytes[..bytes.len() - 24]),
+            Err(ReplayError::Corrupt("input count"))
+        ));
+        assert!(matches!(
+            Replay::read_from(&b"PNG!"[..]),
+            Err(ReplayError::NotAReplay)
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use crate::json_file::{read_versioned, save_atomically, write_versioned, FileError};
+use crate::Game;
+
+/// bumped whenever `Game` changes in a way older saves can't be read as
+pub const SAVE_VERSION: u32 = 1;
+
+/// saving a game partway through and carrying on with it later, everything's
+/// kept including the randomizer's state and the inputs so far for the replay
+impl Game {
+    pub fn write_save(&self, writer: impl Write) -> Result<(), FileError> {
+        write_versioned(writer, SAVE_VERSION, "game", self)
+    }
+
+    pub fn read_save(reader: impl Read) -> Result<Game, FileError> {
+        let game: Game = read_versioned(reader, SAVE_VERSION, "game")?;
+        // it could have been edited into something that'd panic
+        game.check().map_err(FileError::corrupt)?;
+        Ok(game)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FileError> {
+        save_atomically(path.as_ref(), |writer| self.write_save(writer))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Game, FileError> {
+        Game::read_save(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_input::{drops, played};
+    use crate::{RandomizerKind, Settings};
+
+    #[test]
+    fn carries_on_exactly_where_it_left_off() {
+        for randomizer in RandomizerKind::ALL {
+            let settings = Settings {
+                randomizer,
+                ..Settings::default()
+            };
+            let mut inputs = drops();
+            let mut game = played(settings, 21, inputs.by_ref().take(500));
+
+            let mut bytes = Vec::new();
+            game.write_save(&mut bytes).unwrap();
+            // a single line however long the game, not a line per input
+            assert!(!bytes.contains(&b'\n'));
+            let mut loaded = Game::read_save(bytes.as_slice()).unwrap();
+            for input in inputs.take(1000) {
+                game.step(input);
+                loaded.step(input);
+            }
+            assert_eq!(loaded.replay(), game.replay(), "{:?}", randomizer);
+            assert!(loaded.next_queue().eq(game.next_queue()));
+            assert_eq!(loaded.piece_cells(), game.piece_cells());
+        }
+    }
+
+    #[test]
+    fn refuses_other_versions_and_junk() {
+        let mut bytes = Vec::new();
+        Game::with_seed(Settings::default(), 1)
+            .write_save(&mut bytes)
+            .unwrap();
+        let newer = String::from_utf8(bytes)
+            .unwrap()
+            .replacen("\"version\":1", "\"version\":2", 1);
+        assert!(matches!(
+            Game::read_save(newer.as_bytes()),
+            Err(FileError::UnsupportedVersion(2))
+        ));
+        // a game missing everything, and a save cut off partway through
+        assert!(matches!(
+            Game::read_save(&b"{\"version\":1,\"game\":{}}"[..]),
+            Err(FileError::Corrupt(_))
+        ));
+        assert!(matches!(
+            Game::read_save(&b"{\"version\":1,\"ga"[..]),
+            Err(FileError::Corrupt(_))
+        ));
+    }
+
+    // set some fields in a saved game, leaving everything else as it was
+    fn merge(into: &mut serde_json::Value, from: serde_json::Value) {
+        match (into, from) {
+            (serde_json::Value::Object(into), serde_json::Value::Object(from)) => {
+                for (field, value) in from {
+                    merge(into.entry(field).or_insert(serde_json::Value::Null), value);
+                }
+            }
+            (into, from) => *into = from,
+        }
+    }
+
+    fn edited(game: &Game, edit: serde_json::Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        game.write_save(&mut bytes).unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        merge(&mut json["game"], edit);
+        serde_json::to_vec(&json).unwrap()
+    }
+
+    #[test]
+    fn refuses_games_that_would_panic() {
+        let game = played(Settings::default(), 1, drops().take(100));
+        let edits = [
+            serde_json::json!({"next_queue": []}),
+            serde_json::json!({"piece_position_x": 500}),
+            serde_json::json!({"settings": {"das_ms": u32::MAX}}),
+            serde_json::json!({"fade_line_counter": 65535, "line_to_delete": true}),
+            serde_json::json!({"ticks": u32::MAX}),
+            serde_json::json!({"lines": 65535}),
+            serde_json::json!({"lock_delay_counter": u32::MAX}),
+            serde_json::json!({"arr_counter": u32::MAX}),
+            serde_json::json!({"gravity_movement_counter": u32::MAX}),
+        ];
+        for edit in edits {
+            assert!(
+                matches!(
+                    Game::read_save(edited(&game, edit.clone()).as_slice()),
+                    Err(FileError::Corrupt(_))
+                ),
+                "{}",
+                edit
+            );
+        }
+    }
+
+    #[test]
+    fn totals_stop_at_their_limits() {
+        let game = played(Settings::default(), 1, drops().take(100));
+        let level = 1 + u16::MAX / Settings::default().lines_per_level;
+        let edit = serde_json::json!({
+            "score": u32::MAX,
+            "lines": u16::MAX,
+            "level": level,
+            "gravity_speed": Settings::default().gravity_curve.gravity(level),
+        });
+        let mut loaded = Game::read_save(edited(&game, edit).as_slice()).unwrap();
+        for input in drops().skip(100).take(1000) {
+            loaded.step(input);
+        }
+        assert_eq!(loaded.score(), u32::MAX);
+        assert_eq!(loaded.lines(), u16::MAX);
+    }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+
+/// points per row a piece is soft dropped
+pub const SOFT_DROP_POINTS: u32 = 1;
+/// points per row a piece is hard dropped
+pub const HARD_DROP_POINTS: u32 = 2;
+
+// guideline points for clearing 1 to 4 lines at once, times the level
+const LINE_CLEAR_POINTS: [u32; 5] = [0, 100, 300, 500, 800];
+// extra points for clearing the whole board with 1 to 4 lines
+const PERFECT_CLEAR_POINTS: [u32; 5] = [0, 800, 1200, 1800, 2000];
+// a back to back tetris perfect clear is worth more than one and a half times as much
+const BACK_TO_BACK_PERFECT_TETRIS_POINTS: u32 = 3200;
+// a t-spin clearing 0 to 3 lines, times the level
+const T_SPIN_POINTS: [u32; 4] = [400, 800, 1200, 1600];
+// a mini t-spin clearing 0 to 2 lines, it can't fit in a third
+const MINI_T_SPIN_POINTS: [u32; 3] = [100, 200, 400];
+// per clear in a row after the first, times the level
+const COMBO_POINTS: u32 = 50;
+
+/// whether a T piece was spun into place as it locked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Spin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// what happened when a piece locked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clear {
+    /// lines cleared, 0 to 4
+    pub lines: u16,
+    /// nothing left on the board afterwards
+    pub perfect: bool,
+    pub spin: Spin,
+}
+
+impl Clear {
+    /// hard clears keep a back to back going, anything else that
+    /// clears lines breaks it
+    fn difficult(self) -> bool {
+        self.lines == 4 || (self.lines > 0 && self.spin != Spin::None)
+    }
+
+    /// what to call it on screen, if it's worth calling anything
+    pub fn name(self) -> Option<&'static str> {
+        let name = match (self.spin, self.lines) {
+            (Spin::Full, 0) => "T-SPIN",
+            (Spin::Full, 1) => "T-SPIN SINGLE",
+            (Spin::Full, 2) => "T-SPIN DOUBLE",
+            (Spin::Full, _) => "T-SPIN TRIPLE",
+            (Spin::Mini, 0) => "MINI T-SPIN",
+            (Spin::Mini, 1) => "MINI T-SPIN SINGLE",
+            (Spin::Mini, _) => "MINI T-SPIN DOUBLE",
+            (Spin::None, 4) => "TETRIS",
+            (Spin::None, _) => return None,
+        };
+        Some(name)
+    }
+}
+
+/// guideline scoring, remembers what it needs to between pieces
+/// for combos and back to backs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scoring {
+    // clears in a row, none once a piece locks without clearing anything
+    combo: Option<u32>,
+    // the last clear was a difficult one
+    back_to_back: bool,
+}
+
+impl Scoring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// points for a piece locking, and remember it for the next one
+    pub fn lock(&mut self, clear: Clear, level: u16) -> u32 {
+        let level = u32::from(level.max(1));
+        let lines = usize::from(clear.lines.min(4));
+        let mut points = match clear.spin {
+            Spin::None => LINE_CLEAR_POINTS[lines],
+            Spin::Mini => MINI_T_SPIN_POINTS[lines.min(2)],
+            Spin::Full => T_SPIN_POINTS[lines.min(3)],
+        };
+        if lines == 0 {
+            // the combo's over, but a back to back survives pieces that don't clear
+            self.combo = None;
+            return points * level;
+        }
+
+        let back_to_back = clear.difficult() && self.back_to_back;
+        self.back_to_back = clear.difficult();
+
+        if back_to_back {
+            points += points / 2;
+        }
+        if clear.perfect {
+            points += if back_to_back && lines == 4 {
+                BACK_TO_BACK_PERFECT_TETRIS_POINTS
+            } else {
+                PERFECT_CLEAR_POINTS[lines]
+            };
+        }
+
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        self.combo = Some(combo);
+        points += COMBO_POINTS * combo;
+
+        points * level
+    }
+
+    /// clears in a row, counting from 0 for the first one
+    pub fn combo(&self) -> Option<u32> {
+        self.combo
+    }
+
+    /// whether the next difficult clear will be a back to back
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: u16) -> Clear {
+        Clear {
+            lines,
+            ..Clear::default()
+        }
+    }
+
+    fn spin(spin: Spin, lines: u16) -> Clear {
+        Clear {
+            lines,
+            spin,
+            ..Clear::default()
+        }
+    }
+
+    #[test]
+    fn line_clears_times_level() {
+        for (lines, points) in [(0, 0), (1, 100), (2, 300), (3, 500), (4, 800)] {
+            assert_eq!(Scoring::new().lock(clear(lines), 1), points);
+            assert_eq!(Scoring::new().lock(clear(lines), 3), points * 3);
+        }
+    }
+
+    #[test]
+    fn combos_add_up_until_a_piece_clears_nothing() {
+        let mut scoring = Scoring::new();
+        assert_eq!(scoring.lock(clear(1), 2), 200);
+        assert_eq!(scoring.lock(clear(1), 2), 200 + 100);
+        assert_eq!(scoring.lock(clear(2), 2), 600 + 200);
+        assert_eq!(scoring.combo(), Some(2));
+        assert_eq!(scoring.lock(clear(0), 2), 0);
+        assert_eq!(scoring.combo(), None);
+        assert_eq!(scoring.lock(clear(1), 2), 200);
+    }
+
+    #[test]
+    fn back_to_back_tetrises() {
+        let mut scoring = Scoring::new();
+        assert_eq!(scoring.lock(clear(4), 1), 800);
+        // pieces that don't clear anything don't break it
+        scoring.lock(clear(0), 1);
+        assert_eq!(scoring.lock(clear(4), 1), 1200);
+        scoring.lock(clear(0), 1);
+        assert!(scoring.back_to_back());
+        // but an easy clear does
+        assert_eq!(scoring.lock(clear(1), 1), 100);
+        scoring.lock(clear(0), 1);
+        assert_eq!(scoring.lock(clear(4), 1), 800);
+    }
+
+    #[test]
+    fn perfect_clears() {
+        let perfect = |lines| Clear {
+            lines,
+            perfect: true,
+            ..Clear::default()
+        };
+        assert_eq!(Scoring::new().lock(perfect(1), 1), 100 + 800);
+        assert_eq!(Scoring::new().lock(perfect(4), 2), (800 + 2000) * 2);
+
+        let mut scoring = Scoring::new();
+        scoring.lock(clear(4), 1);
+        scoring.lock(clear(0), 1);
+        assert_eq!(scoring.lock(perfect(4), 1), 1200 + 3200);
+    }
+
+    #[test]
+    fn t_spins() {
+        let table = [
+            (spin(Spin::Full, 0), 400),
+            (spin(Spin::Full, 1), 800),
+            (spin(Spin::Full, 2), 1200),
+            (spin(Spin::Full, 3), 1600),
+            (spin(Spin::Mini, 0), 100),
+            (spin(Spin::Mini, 1), 200),
+            (spin(Spin::Mini, 2), 400),
+        ];
+        for (clear, points) in table {
+            assert_eq!(Scoring::new().lock(clear, 2), points * 2, "{:?}", clear);
+        }
+    }
+
+    #[test]
+    fn t_spins_and_tetrises_go_back_to_back() {
+        let mut scoring = Scoring::new();
+        assert_eq!(scoring.lock(clear(4), 1), 800);
+        scoring.lock(clear(0), 1);
+        assert_eq!(scoring.lock(spin(Spin::Mini, 1), 1), 300);
+        scoring.lock(clear(0), 1);
+        // a spin that doesn't clear anything doesn't break it either
+        assert_eq!(scoring.lock(spin(Spin::Full, 0), 1), 400);
+        assert_eq!(scoring.lock(spin(Spin::Full, 2), 1), 1800);
+        assert!(scoring.back_to_back());
+    }
+
+    #[test]
+    fn callout_names() {
+        assert_eq!(spin(Spin::Full, 2).name(), Some("T-SPIN DOUBLE"));
+        assert_eq!(spin(Spin::Mini, 0).name(), Some("MINI T-SPIN"));
+        assert_eq!(clear(4).name(), Some("TETRIS"));
+        assert_eq!(clear(3).name(), None);
+    }
+}
@@ -0,0 +1,503 @@
+use crate::menu::{ResultsItem, TitleItem};
+use crate::{GameMode, Menu, PauseItem, Settings, SettingsItem, MAX_NAME_LENGTH};
+
+/// buttons for getting round the menus, separate from the game's own buttons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    /// pauses while playing, and backs out of everything else
+    Back,
+    /// pauses and resumes, and does nothing anywhere else
+    Pause,
+}
+
+/// something the front-end has to do because the screen changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// throw away the current game and start a fresh one with `App::settings`
+    NewGame,
+    /// carry on with the saved game instead
+    Continue,
+    /// write the finished game's replay to disk
+    SaveReplay,
+    /// start playing back the finished game's replay
+    WatchReplay,
+    /// put the finished game on the leaderboard under `App::name`
+    RecordScore,
+    Quit,
+}
+
+/// every screen there is, and the menu on it if it's got one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Title(Menu<TitleItem>),
+    ModeSelect(Menu<GameMode>),
+    /// the only screen the game ticks on
+    Playing,
+    Paused(Menu<PauseItem>),
+    /// `from_pause` is where to go back to, the pause menu or the title
+    Settings {
+        menu: Menu<SettingsItem>,
+        from_pause: bool,
+    },
+    /// typing in a name for the leaderboard, before the results
+    NameEntry,
+    Results(Menu<ResultsItem>),
+    /// the leaderboard for a mode, left and right go through the others
+    HighScores(GameMode),
+    /// playing back a replay, which has its own controls
+    Replay,
+}
+
+/// everything around the game itself: which screen is up and the settings
+/// the next game will use, moved between screens with `MenuKey`s
+#[derive(Clone, Debug)]
+pub struct App {
+    screen: Screen,
+    settings: Settings,
+    // the settings of the saved game there is to carry on with, if there is one
+    saved: Option<Settings>,
+    // for the leaderboard, kept so it's there already next time
+    name: String,
+}
+
+impl App {
+    /// starts on the title screen
+    pub fn new(settings: Settings) -> Self {
+        App {
+            screen: Screen::Title(Menu::new(&TitleItem::ALL)),
+            settings,
+            saved: None,
+            name: String::new(),
+        }
+    }
+
+    /// put continue on the title screen, for when there's a saved game,
+    /// which carries on with the settings it was saved with
+    pub fn offer_continue(&mut self, settings: Settings) {
+        self.saved = Some(settings);
+        if let Screen::Title(_) = self.screen {
+            self.title();
+        }
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// whether the game should be stepped this frame
+    pub fn is_playing(&self) -> bool {
+        self.screen == Screen::Playing
+    }
+
+    /// whether there's a game going that hasn't finished or been quit,
+    /// even if it's paused, which is worth saving when the window closes
+    pub fn in_game(&self) -> bool {
+        matches!(
+            self.screen,
+            Screen::Playing
+                | Screen::Paused(_)
+                | Screen::Settings {
+                    from_pause: true,
+                    ..
+                }
+        )
+    }
+
+    /// handle a menu button, and say if the front-end needs to do anything about it
+    pub fn press(&mut self, key: MenuKey) -> Option<Command> {
+        match &mut self.screen {
+            Screen::Title(menu) => match key {
+                MenuKey::Confirm => match menu.selected() {
+                    TitleItem::Continue => {
+                        // it's only saved once, so there's nothing to continue after this,
+                        // and restarting should play the same kind of game again
+                        if let Some(settings) = self.saved.take() {
+                            self.settings = settings;
+                        }
+                        self.screen = Screen::Playing;
+                        return Some(Command::Continue);
+                    }
+                    TitleItem::Play => self.screen = Screen::ModeSelect(Menu::new(&GameMode::ALL)),
+                    TitleItem::HighScores => self.screen = Screen::HighScores(self.settings.mode),
+                    TitleItem::Settings => self.open_settings(false),
+                    TitleItem::Quit => return Some(Command::Quit),
+                },
+                MenuKey::Back => return Some(Command::Quit),
+                _ => move_cursor(menu, key),
+            },
+            Screen::ModeSelect(menu) => match key {
+                MenuKey::Confirm => {
+                    self.settings.mode = menu.selected();
+                    return Some(self.start());
+                }
+                MenuKey::Back => self.title(),
+                _ => move_cursor(menu, key),
+            },
+            // everything else is game buttons, which the game handles itself
+            Screen::Playing => {
+                if let MenuKey::Back | MenuKey::Pause = key {
+                    self.screen = Screen::Paused(Menu::new(&PauseItem::ALL));
+                }
+            }
+            Screen::Paused(menu) => match key {
+                MenuKey::Confirm => match menu.selected() {
+                    PauseItem::Resume => self.screen = Screen::Playing,
+                    PauseItem::Restart => return Some(self.start()),
+                    PauseItem::Settings => self.open_settings(true),
+                    PauseItem::QuitToTitle => self.title(),
+                },
+                MenuKey::Back | MenuKey::Pause => self.screen = Screen::Playing,
+                _ => move_cursor(menu, key),
+            },
+            Screen::Settings { menu, from_pause } => match key {
+                MenuKey::Left => self.settings.adjust(menu.selected(), -1),
+                MenuKey::Right => self.settings.adjust(menu.selected(), 1),
+                MenuKey::Confirm | MenuKey::Back => {
+                    if *from_pause {
+                        self.screen = Screen::Paused(Menu::new(&PauseItem::ALL));
+                    } else {
+                        self.title();
+                    }
+                }
+                _ => move_cursor(menu, key),
+            },
+            Screen::NameEntry => match key {
+                MenuKey::Confirm if !self.name.trim().is_empty() => {
+                    self.screen = Screen::Results(Menu::new(&ResultsItem::ALL));
+                    return Some(Command::RecordScore);
+                }
+                // skip it, the score doesn't go on the board
+                MenuKey::Back => self.screen = Screen::Results(Menu::new(&ResultsItem::ALL)),
+                _ => {}
+            },
+            Screen::Results(menu) => match key {
+                MenuKey::Confirm => match menu.selected() {
+                    ResultsItem::PlayAgain => return Some(self.start()),
+                    ResultsItem::WatchReplay => {
+                        self.watch();
+                        return Some(Command::WatchReplay);
+                    }
+                    ResultsItem::SaveReplay => return Some(Command::SaveReplay),
+                    ResultsItem::QuitToTitle => self.title(),
+                },
+                MenuKey::Back => self.title(),
+                _ => move_cursor(menu, key),
+            },
+            Screen::HighScores(mode) => match key {
+                MenuKey::Left | MenuKey::Right => {
+                    let index = GameMode::ALL.iter().position(|other| other == mode);
+                    let step = if key == MenuKey::Left {
+                        GameMode::ALL.len() - 1
+                    } else {
+                        1
+                    };
+                    *mode = GameMode::ALL[(index.unwrap_or(0) + step) % GameMode::ALL.len()];
+                }
+                MenuKey::Confirm | MenuKey::Back => self.title(),
+                _ => {}
+            },
+            Screen::Replay => {
+                if key == MenuKey::Back {
+                    self.title();
+                }
+            }
+        }
+        None
+    }
+
+    /// switch to watching a replay, from the results or straight from the command line
+    pub fn watch(&mut self) {
+        self.screen = Screen::Replay;
+    }
+
+    /// the game's finished, show how it went
+    pub fn game_over(&mut self) {
+        if self.screen == Screen::Playing {
+            self.screen = Screen::Results(Menu::new(&ResultsItem::ALL));
+        }
+    }
+
+    /// the game's finished well enough to go on the leaderboard,
+    /// ask for a name before showing how it went
+    pub fn new_high_score(&mut self) {
+        if self.screen == Screen::Playing {
+            self.screen = Screen::NameEntry;
+        }
+    }
+
+    /// the name typed in for the leaderboard so far
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// a key typed while entering a name, anything that isn't a letter,
+    /// number or space is ignored
+    pub fn type_name(&mut self, c: char) {
+        let allowed = c.is_ascii_alphanumeric() || (c == ' ' && !self.name.is_empty());
+        if self.screen == Screen::NameEntry && allowed && self.name.len() < MAX_NAME_LENGTH {
+            self.name.push(c.to_ascii_uppercase());
+        }
+    }
+
+    /// backspace while entering a name
+    pub fn erase_name(&mut self) {
+        if self.screen == Screen::NameEntry {
+            self.name.pop();
+        }
+    }
+
+    fn start(&mut self) -> Command {
+        self.screen = Screen::Playing;
+        Command::NewGame
+    }
+
+    fn title(&mut self) {
+        let items: &'static [TitleItem] = if self.saved.is_some() {
+            &TitleItem::WITH_CONTINUE
+        } else {
+            &TitleItem::ALL
+        };
+        self.screen = Screen::Title(Menu::new(items));
+    }
+
+    fn open_settings(&mut self, from_pause: bool) {
+        self.screen = Screen::Settings {
+            menu: Menu::new(&SettingsItem::ALL),
+            from_pause,
+        };
+    }
+}
+
+/// up and down move through a menu, nothing else does
+fn move_cursor<T: Copy>(menu: &mut Menu<T>, key: MenuKey) {
+    match key {
+        MenuKey::Up => menu.up(),
+        MenuKey::Down => menu.down(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press_all(app: &mut App, keys: &[MenuKey]) -> Vec<Command> {
+        keys.iter().filter_map(|&key| app.press(key)).collect()
+    }
+
+    #[test]
+    fn title_to_playing() {
+        let mut app = App::new(Settings::default());
+        assert!(matches!(app.screen(), Screen::Title(_)));
+        assert_eq!(app.press(MenuKey::Confirm), None);
+        assert!(matches!(app.screen(), Screen::ModeSelect(_)));
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::NewGame));
+        assert!(app.is_playing());
+        assert_eq!(app.settings().mode, GameMode::Marathon);
+    }
+
+    #[test]
+    fn pausing_stops_play_until_resumed() {
+        let mut app = App::new(Settings::default());
+        press_all(&mut app, &[MenuKey::Confirm, MenuKey::Confirm]);
+
+        // game buttons don't do anything to the screens
+        press_all(&mut app, &[MenuKey::Up, MenuKey::Left, MenuKey::Confirm]);
+        assert!(app.is_playing());
+
+        app.press(MenuKey::Back);
+        assert!(matches!(app.screen(), Screen::Paused(_)));
+        assert!(!app.is_playing());
+        app.press(MenuKey::Back);
+        assert!(app.is_playing());
+
+        // resume from the menu too
+        press_all(&mut app, &[MenuKey::Back, MenuKey::Confirm]);
+        assert!(app.is_playing());
+
+        // and the pause key goes both ways
+        app.press(MenuKey::Pause);
+        assert!(matches!(app.screen(), Screen::Paused(_)));
+        app.press(MenuKey::Pause);
+        assert!(app.is_playing());
+    }
+
+    #[test]
+    fn pause_key_does_nothing_off_the_board() {
+        let mut app = App::new(Settings::default());
+        assert_eq!(app.press(MenuKey::Pause), None);
+        assert!(matches!(app.screen(), Screen::Title(_)));
+
+        app.watch();
+        app.press(MenuKey::Pause);
+        assert_eq!(app.screen(), &Screen::Replay);
+    }
+
+    #[test]
+    fn settings_from_pause_go_back_to_pause() {
+        let mut app = App::new(Settings::default());
+        press_all(
+            &mut app,
+            &[MenuKey::Confirm, MenuKey::Confirm, MenuKey::Back],
+        );
+        // resume, restart, settings
+        press_all(&mut app, &[MenuKey::Down, MenuKey::Down, MenuKey::Confirm]);
+        assert!(matches!(
+            app.screen(),
+            Screen::Settings {
+                from_pause: true,
+                ..
+            }
+        ));
+
+        // first setting is the randomizer
+        app.press(MenuKey::Right);
+        assert_ne!(app.settings().randomizer, Settings::default().randomizer);
+        app.press(MenuKey::Back);
+        assert!(matches!(app.screen(), Screen::Paused(_)));
+    }
+
+    #[test]
+    fn restart_and_quit_to_title() {
+        let mut app = App::new(Settings::default());
+        press_all(
+            &mut app,
+            &[MenuKey::Confirm, MenuKey::Confirm, MenuKey::Back],
+        );
+        let commands = press_all(&mut app, &[MenuKey::Down, MenuKey::Confirm]);
+        assert_eq!(commands, [Command::NewGame]);
+        assert!(app.is_playing());
+
+        press_all(&mut app, &[MenuKey::Back, MenuKey::Up, MenuKey::Confirm]);
+        assert!(matches!(app.screen(), Screen::Title(_)));
+        // and escape on the title quits
+        assert_eq!(app.press(MenuKey::Back), Some(Command::Quit));
+    }
+
+    #[test]
+    fn game_over_shows_results() {
+        let mut app = App::new(Settings::default());
+        // only a game being played can end
+        app.game_over();
+        assert!(matches!(app.screen(), Screen::Title(_)));
+
+        press_all(&mut app, &[MenuKey::Confirm, MenuKey::Confirm]);
+        app.game_over();
+        assert!(matches!(app.screen(), Screen::Results(_)));
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::NewGame));
+        assert!(app.is_playing());
+
+        app.game_over();
+        let commands = press_all(&mut app, &[MenuKey::Up, MenuKey::Up, MenuKey::Confirm]);
+        assert_eq!(commands, [Command::SaveReplay]);
+        assert!(matches!(app.screen(), Screen::Results(_)));
+        press_all(&mut app, &[MenuKey::Down, MenuKey::Confirm]);
+        assert!(matches!(app.screen(), Screen::Title(_)));
+    }
+
+    #[test]
+    fn watching_a_replay() {
+        let mut app = App::new(Settings::default());
+        press_all(&mut app, &[MenuKey::Confirm, MenuKey::Confirm]);
+        app.game_over();
+        let commands = press_all(&mut app, &[MenuKey::Down, MenuKey::Confirm]);
+        assert_eq!(commands, [Command::WatchReplay]);
+        assert_eq!(app.screen(), &Screen::Replay);
+        assert!(!app.is_playing());
+        // playback keys are the front-end's business, only back does anything
+        press_all(&mut app, &[MenuKey::Confirm, MenuKey::Up]);
+        assert_eq!(app.screen(), &Screen::Replay);
+        app.press(MenuKey::Back);
+        assert!(matches!(app.screen(), Screen::Title(_)));
+    }
+
+    #[test]
+    fn continuing_a_saved_game() {
+        let mut app = App::new(Settings::default());
+        let sprint = Settings {
+            mode: GameMode::Sprint,
+            das_ms: 100,
+            ..Settings::default()
+        };
+        app.offer_continue(sprint);
+        assert!(!app.in_game());
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::Continue));
+        assert!(app.is_playing());
+
+        app.press(MenuKey::Back);
+        assert!(app.in_game());
+        press_all(&mut app, &[MenuKey::Down, MenuKey::Down, MenuKey::Confirm]);
+        assert!(app.in_game());
+        // it's been used up, so the title's back to starting with play
+        press_all(&mut app, &[MenuKey::Back, MenuKey::Up, MenuKey::Confirm]);
+        assert!(!app.in_game());
+        assert_eq!(app.press(MenuKey::Confirm), None);
+        assert!(matches!(app.screen(), Screen::ModeSelect(_)));
+    }
+
+    #[test]
+    fn entering_a_name_for_a_high_score() {
+        let mut app = App::new(Settings::default());
+        // only a game being played can end
+        app.new_high_score();
+        assert!(matches!(app.screen(), Screen::Title(_)));
+
+        press_all(&mut app, &[MenuKey::Confirm, MenuKey::Confirm]);
+        app.new_high_score();
+        assert_eq!(app.screen(), &Screen::NameEntry);
+        // needs a name first
+        assert_eq!(app.press(MenuKey::Confirm), None);
+        for c in " ab-c 1234567890".chars() {
+            app.type_name(c);
+        }
+        app.erase_name();
+        assert_eq!(app.name(), "ABC 1234567");
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::RecordScore));
+        assert!(matches!(app.screen(), Screen::Results(_)));
+
+        // skipping it still shows the results, and the name's kept for next time
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::NewGame));
+        app.new_high_score();
+        assert_eq!(app.name(), "ABC 1234567");
+        assert_eq!(app.press(MenuKey::Back), None);
+        assert!(matches!(app.screen(), Screen::Results(_)));
+    }
+
+    #[test]
+    fn high_scores_go_through_every_mode() {
+        let mut app = App::new(Settings::default());
+        press_all(&mut app, &[MenuKey::Down, MenuKey::Confirm]);
+        assert_eq!(app.screen(), &Screen::HighScores(GameMode::Marathon));
+        app.press(MenuKey::Left);
+        assert_eq!(app.screen(), &Screen::HighScores(GameMode::Ultra));
+        press_all(&mut app, &[MenuKey::Right, MenuKey::Right]);
+        assert_eq!(app.screen(), &Screen::HighScores(GameMode::Endless));
+        app.press(MenuKey::Back);
+        assert!(matches!(app.screen(), Screen::Title(_)));
+    }
+
+    #[test]
+    fn restarting_a_continued_game_keeps_its_settings() {
+        let mut app = App::new(Settings::default());
+        let ultra = Settings {
+            mode: GameMode::Ultra,
+            arr_ms: 0,
+            ..Settings::default()
+        };
+        app.offer_continue(ultra);
+        assert_eq!(app.press(MenuKey::Confirm), Some(Command::Continue));
+        // pause, then restart
+        let commands = press_all(&mut app, &[MenuKey::Back, MenuKey::Down, MenuKey::Confirm]);
+        assert_eq!(commands, [Command::NewGame]);
+        assert_eq!(app.settings(), &ultra);
+    }
+}
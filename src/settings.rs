@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{ms_to_ticks, ticks_to_ms};
+use crate::{GameMode, GravityCurve, RandomizerKind};
+
+/// most upcoming pieces the next queue can show
+pub const MAX_NEXT_QUEUE: usize = 6;
+
+/// everything about a game that's picked before it starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// picked on the mode select screen rather than the settings one
+    pub mode: GameMode,
+    pub randomizer: RandomizerKind,
+    /// how many upcoming pieces are shown, 1 to `MAX_NEXT_QUEUE`
+    pub next_queue: usize,
+    /// show where the piece in flight will land
+    pub ghost: bool,
+    /// how long a piece can sit on the stack before it locks
+    pub lock_delay_ms: u32,
+    /// how many times moving or turning can restart the lock delay,
+    /// so a piece can't be spun on the spot forever
+    pub lock_resets: u16,
+    /// delayed auto shift, how long left or right has to be held before it repeats
+    pub das_ms: u32,
+    /// auto repeat rate, time between each repeated move, 0 goes straight to the wall
+    pub arr_ms: u32,
+    /// how many times faster than gravity a soft drop falls
+    pub soft_drop_factor: u16,
+    /// how fast pieces fall at each level
+    pub gravity_curve: GravityCurve,
+    /// lines to clear for each level up
+    pub lines_per_level: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            mode: GameMode::default(),
+            randomizer: RandomizerKind::default(),
+            next_queue: 5,
+            ghost: true,
+            lock_delay_ms: 500,
+            lock_resets: 15,
+            das_ms: 167,
+            arr_ms: 33,
+            soft_drop_factor: 20,
+            gravity_curve: GravityCurve::default(),
+            lines_per_level: 10,
+        }
+    }
+}
+
+/// the settings that can be changed from the settings screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsItem {
+    Randomizer,
+    NextQueue,
+    Ghost,
+    Das,
+    Arr,
+    SoftDropFactor,
+    GravityCurve,
+    LinesPerLevel,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 8] = [
+        SettingsItem::Randomizer,
+        SettingsItem::NextQueue,
+        SettingsItem::Ghost,
+        SettingsItem::Das,
+        SettingsItem::Arr,
+        SettingsItem::SoftDropFactor,
+        SettingsItem::GravityCurve,
+        SettingsItem::LinesPerLevel,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsItem::Randomizer => "RANDOMIZER",
+            SettingsItem::NextQueue => "NEXT PIECES",
+            SettingsItem::Ghost => "GHOST",
+            SettingsItem::Das => "DAS",
+            SettingsItem::Arr => "ARR",
+            SettingsItem::SoftDropFactor => "SOFT DROP",
+            SettingsItem::GravityCurve => "GRAVITY",
+            SettingsItem::LinesPerLevel => "LINES PER LEVEL",
+        }
+    }
+}
+
+const MAX_DAS_MS: u32 = 500;
+const MAX_ARR_MS: u32 = 200;
+const MAX_SOFT_DROP_FACTOR: u16 = 40;
+const MAX_LINES_PER_LEVEL: u16 = 50;
+// not on the settings screen, but anything longer than this isn't a lock delay
+const MAX_LOCK_DELAY_MS: u32 = 10_000;
+
+impl Settings {
+    /// nudge one setting up (positive) or down (negative) a step,
+    /// going round in a loop for the ones that are a choice of names
+    pub fn adjust(&mut self, item: SettingsItem, direction: i32) {
+        let up = direction > 0;
+        match item {
+            SettingsItem::Randomizer => {
+                self.randomizer = cycle(&RandomizerKind::ALL, self.randomizer, up)
+            }
+            SettingsItem::NextQueue => {
+                self.next_queue = step(self.next_queue, 1, up).clamp(1, MAX_NEXT_QUEUE)
+            }
+            SettingsItem::Ghost => self.ghost = !self.ghost,
+            SettingsItem::Das => self.das_ms = step_tick(self.das_ms, up).min(MAX_DAS_MS),
+            SettingsItem::Arr => self.arr_ms = step_tick(self.arr_ms, up).min(MAX_ARR_MS),
+            SettingsItem::SoftDropFactor => {
+                self.soft_drop_factor =
+                    step(self.soft_drop_factor, 1, up).clamp(1, MAX_SOFT_DROP_FACTOR)
+            }
+            SettingsItem::GravityCurve => {
+                self.gravity_curve = cycle(&GravityCurve::ALL, self.gravity_curve, up)
+            }
+            SettingsItem::LinesPerLevel => {
+                self.lines_per_level =
+                    step(self.lines_per_level, 1, up).clamp(1, MAX_LINES_PER_LEVEL)
+            }
+        }
+    }
+
+    /// the first setting that's outside what the settings screen allows, if any,
+    /// for anything that comes from outside like the command line or a replay
+    pub fn check(&self) -> Result<(), &'static str> {
+        if !(1..=MAX_NEXT_QUEUE).contains(&self.next_queue) {
+            Err("next queue")
+        } else if self.lock_delay_ms > MAX_LOCK_DELAY_MS {
+            Err("lock delay")
+        } else if self.das_ms > MAX_DAS_MS {
+            Err("das")
+        } else if self.arr_ms > MAX_ARR_MS {
+            Err("arr")
+        } else if !(1..=MAX_SOFT_DROP_FACTOR).contains(&self.soft_drop_factor) {
+            Err("soft drop factor")
+        } else if !(1..=MAX_LINES_PER_LEVEL).contains(&self.lines_per_level) {
+            Err("lines per level")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// one setting as it's shown on the settings screen
+    pub fn value(&self, item: SettingsItem) -> String {
+        match item {
+            SettingsItem::Randomizer => self.randomizer.name().to_uppercase(),
+            SettingsItem::NextQueue => self.next_queue.to_string(),
+            SettingsItem::Ghost => if self.ghost { "ON" } else { "OFF" }.to_string(),
+            SettingsItem::Das => format!("{} MS", self.das_ms),
+            SettingsItem::Arr => format!("{} MS", self.arr_ms),
+            SettingsItem::SoftDropFactor => format!("X{}", self.soft_drop_factor),
+            SettingsItem::GravityCurve => self.gravity_curve.name().to_uppercase(),
+            SettingsItem::LinesPerLevel => self.lines_per_level.to_string(),
+        }
+    }
+}
+
+/// a number a step up or down, stopping at 0
+fn step<T>(value: T, by: T, up: bool) -> T
+where
+    T: std::ops::Add<Output = T> + std::ops::Sub<Output = T> + Ord + Copy + Default,
+{
+    if up {
+        value + by
+    } else if value > by {
+        value - by
+    } else {
+        T::default()
+    }
+}
+
+// handling goes up and down a whole tick at a time, whatever it started at
+fn step_tick(ms: u32, up: bool) -> u32 {
+    ticks_to_ms(step(ms_to_ticks(ms), 1, up))
+}
+
+/// the next or previous choice, wrapping round at either end
+fn cycle<T: PartialEq + Copy>(all: &[T], current: T, forwards: bool) -> T {
+    let index = all.iter().position(|&item| item == current).unwrap_or(0);
+    let next = if forwards {
+        (index + 1) % all.len()
+    } else {
+        (index + all.len() - 1) % all.len()
+    };
+    all[next]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusting_stays_in_range() {
+        let mut settings = Settings::default();
+        for _ in 0..50 {
+            settings.adjust(SettingsItem::NextQueue, 1);
+            settings.adjust(SettingsItem::Arr, -1);
+            settings.adjust(SettingsItem::SoftDropFactor, -1);
+        }
+        assert_eq!(settings.next_queue, MAX_NEXT_QUEUE);
+        assert_eq!(settings.arr_ms, 0);
+        assert_eq!(settings.soft_drop_factor, 1);
+
+        // a tick at a time, 167 ms is 10 ticks
+        settings.adjust(SettingsItem::Das, 1);
+        assert_eq!(settings.das_ms, 183);
+        assert_eq!(ms_to_ticks(settings.das_ms), 11);
+        for _ in 0..50 {
+            settings.adjust(SettingsItem::Das, 1);
+        }
+        assert_eq!(settings.das_ms, 500);
+    }
+
+    #[test]
+    fn checking_catches_anything_out_of_range() {
+        assert_eq!(Settings::default().check(), Ok(()));
+        let bad = [
+            (
+                Settings {
+                    das_ms: u32::MAX,
+                    ..Settings::default()
+                },
+                "das",
+            ),
+            (
+                Settings {
+                    soft_drop_factor: 0,
+                    ..Settings::default()
+                },
+                "soft drop factor",
+            ),
+            (
+                Settings {
+                    lock_delay_ms: 100_000,
+                    ..Settings::default()
+                },
+                "lock delay",
+            ),
+        ];
+        for (settings, what) in bad {
+            assert_eq!(settings.check(), Err(what));
+        }
+    }
+
+    #[test]
+    fn choices_wrap_round() {
+        let mut settings = Settings::default();
+        settings.adjust(SettingsItem::Randomizer, -1);
+        assert_eq!(settings.randomizer, RandomizerKind::Tgm);
+        settings.adjust(SettingsItem::Randomizer, 1);
+        assert_eq!(settings.randomizer, RandomizerKind::SevenBag);
+        settings.adjust(SettingsItem::Ghost, 1);
+        assert_eq!(settings.value(SettingsItem::Ghost), "OFF");
+    }
+}
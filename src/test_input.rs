@@ -0,0 +1,52 @@
+//! made up inputs for the tests, the same every run
+
+use crate::{Game, InputFrame, Settings};
+
+/// buttons mashed at random, from an xorshift so nothing changes between runs
+pub(crate) fn mash() -> impl Iterator<Item = InputFrame> {
+    let mut state: u32 = 0x2545_f491;
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        InputFrame {
+            left: state & 0x3 == 0,
+            right: state & 0xc == 0,
+            rotate_cw: state & 0x30 == 0,
+            rotate_ccw: state & 0xc0 == 0,
+            rotate_180: state & 0x300 == 0,
+            soft_drop: state & 0x400 != 0,
+            hold: state & 0x1800 == 0,
+            hard_drop: state & 0x1e000 == 0,
+        }
+    })
+}
+
+/// a piece every 40 ticks, tapped over to a different column each time
+/// so it takes a while to top out
+pub(crate) fn drops() -> impl Iterator<Item = InputFrame> {
+    (0usize..).map(|tick| {
+        let (piece, offset) = (tick / 40, tick % 40);
+        let shift = piece % 9;
+        let tap = offset % 2 == 0 && offset < 2 * shift.abs_diff(4);
+        InputFrame {
+            left: tap && shift < 4,
+            right: tap && shift > 4,
+            hard_drop: offset == 30,
+            ..InputFrame::default()
+        }
+    })
+}
+
+/// a new game that's been given some inputs
+pub(crate) fn played(
+    settings: Settings,
+    seed: u64,
+    inputs: impl IntoIterator<Item = InputFrame>,
+) -> Game {
+    let mut game = Game::with_seed(settings, seed);
+    for input in inputs {
+        game.step(input);
+    }
+    game
+}
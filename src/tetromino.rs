@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Color;
+
+/// the seven tetromino kinds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Tetromino {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+/// the four rotation states, named after SRS (spawn, right, reverse, left)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Rotation {
+    Spawn,
+    Right,
+    Reverse,
+    Left,
+}
+
+impl Rotation {
+    /// the state after a clockwise turn
+    pub fn cw(self) -> Rotation {
+        match self {
+            Rotation::Spawn => Rotation::Right,
+            Rotation::Right => Rotation::Reverse,
+            Rotation::Reverse => Rotation::Left,
+            Rotation::Left => Rotation::Spawn,
+        }
+    }
+
+    /// the state after a counter-clockwise turn
+    pub fn ccw(self) -> Rotation {
+        match self {
+            Rotation::Spawn => Rotation::Left,
+            Rotation::Right => Rotation::Spawn,
+            Rotation::Reverse => Rotation::Right,
+            Rotation::Left => Rotation::Reverse,
+        }
+    }
+
+    /// the state after a half turn
+    pub fn flip(self) -> Rotation {
+        self.cw().cw()
+    }
+}
+
+/// everything there is to know about one kind of piece
+struct Definition {
+    color: Color,
+    // (x, y) of each square inside a 4x4 box, y going down,
+    // one entry per rotation state in the order of `Rotation`
+    rotations: [[(i32, i32); 4]; 4],
+}
+
+// indexed by `Tetromino as usize`
+// shapes are the standard SRS ones, so the box spins around the same
+// centre as every other modern tetris
+const DEFINITIONS: [Definition; 7] = [
+    // I (straight)
+    Definition {
+        color: Color::SKYBLUE,
+        rotations: [
+            [(0, 1), (1, 1), (2, 1), (3, 1)],
+            [(2, 0), (2, 1), (2, 2), (2, 3)],
+            [(0, 2), (1, 2), (2, 2), (3, 2)],
+            [(1, 0), (1, 1), (1, 2), (1, 3)],
+        ],
+    },
+    // O (square)
+    Definition {
+        color: Color::YELLOW,
+        rotations: [
+            [(1, 0), (2, 0), (1, 1), (2, 1)],
+            [(1, 0), (2, 0), (1, 1), (2, 1)],
+            [(1, 0), (2, 0), (1, 1), (2, 1)],
+            [(1, 0), (2, 0), (1, 1), (2, 1)],
+        ],
+    },
+    // T (cross cut)
+    Definition {
+        color: Color::PURPLE,
+        rotations: [
+            [(1, 0), (0, 1), (1, 1), (2, 1)],
+            [(1, 0), (1, 1), (2, 1), (1, 2)],
+            [(0, 1), (1, 1), (2, 1), (1, 2)],
+            [(1, 0), (0, 1), (1, 1), (1, 2)],
+        ],
+    },
+    // S
+    Definition {
+        color: Color::GREEN,
+        rotations: [
+            [(1, 0), (2, 0), (0, 1), (1, 1)],
+            [(1, 0), (1, 1), (2, 1), (2, 2)],
+            [(1, 1), (2, 1), (0, 2), (1, 2)],
+            [(0, 0), (0, 1), (1, 1), (1, 2)],
+        ],
+    },
+    // Z (inverted S)
+    Definition {
+        color: Color::RED,
+        rotations: [
+            [(0, 0), (1, 0), (1, 1), (2, 1)],
+            [(2, 0), (1, 1), (2, 1), (1, 2)],
+            [(0, 1), (1, 1), (1, 2), (2, 2)],
+            [(1, 0), (0, 1), (1, 1), (0, 2)],
+        ],
+    },
+    // J (inverted L)
+    Definition {
+        color: Color::BROWN,
+        rotations: [
+            [(0, 0), (0, 1), (1, 1), (2, 1)],
+            [(1, 0), (2, 0), (1, 1), (1, 2)],
+            [(0, 1), (1, 1), (2, 1), (2, 2)],
+            [(1, 0), (1, 1), (0, 2), (1, 2)],
+        ],
+    },
+    // L
+    Definition {
+        color: Color::BLUE,
+        rotations: [
+            [(2, 0), (0, 1), (1, 1), (2, 1)],
+            [(1, 0), (1, 1), (1, 2), (2, 2)],
+            [(0, 1), (1, 1), (2, 1), (0, 2)],
+            [(0, 0), (1, 0), (1, 1), (1, 2)],
+        ],
+    },
+];
+
+// SRS wall kicks, tried in order until one fits
+// these are the usual published tables but with y flipped, since our y goes down
+// indexed by `kick_index`
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // 0 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // R -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // R -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // 2 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // 2 -> L
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // L -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // L -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // 0 -> L
+];
+
+const I_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)], // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)], // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)], // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)], // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)], // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)], // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)], // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)], // 0 -> L
+];
+
+// SRS has no half turns, these are the widely used ones from SRS+
+// indexed by the state being turned from
+const HALF_TURN_KICKS: [[(i32, i32); 6]; 4] = [
+    [(0, 0), (0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)], // 0 -> 2
+    [(0, 0), (1, 0), (1, -2), (1, -1), (0, -2), (0, -1)],  // R -> L
+    [(0, 0), (0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)],    // 2 -> 0
+    [(0, 0), (-1, 0), (-1, -2), (-1, -1), (0, -2), (0, -1)], // L -> R
+];
+
+// the O piece never needs to move when it turns
+const NO_KICKS: [(i32, i32); 1] = [(0, 0)];
+
+/// row of the kick tables for a turn between two neighbouring states
+fn kick_index(from: Rotation, to: Rotation) -> usize {
+    match (from, to) {
+        (Rotation::Spawn, Rotation::Right) => 0,
+        (Rotation::Right, Rotation::Spawn) => 1,
+        (Rotation::Right, Rotation::Reverse) => 2,
+        (Rotation::Reverse, Rotation::Right) => 3,
+        (Rotation::Reverse, Rotation::Left) => 4,
+        (Rotation::Left, Rotation::Reverse) => 5,
+        (Rotation::Left, Rotation::Spawn) => 6,
+        (Rotation::Spawn, Rotation::Left) => 7,
+        _ => panic!("no kicks from {:?} to {:?}", from, to),
+    }
+}
+
+impl Tetromino {
+    pub const ALL: [Tetromino; 7] = [
+        Tetromino::I,
+        Tetromino::O,
+        Tetromino::T,
+        Tetromino::S,
+        Tetromino::Z,
+        Tetromino::J,
+        Tetromino::L,
+    ];
+
+    fn definition(self) -> &'static Definition {
+        &DEFINITIONS[self as usize]
+    }
+
+    pub fn color(self) -> Color {
+        self.definition().color
+    }
+
+    /// (x, y) of the four squares inside the piece's 4x4 box
+    pub fn cells(self, rotation: Rotation) -> [(i32, i32); 4] {
+        self.definition().rotations[rotation as usize]
+    }
+
+    /// offsets to try, in order, when turning from one state to another
+    pub fn kicks(self, from: Rotation, to: Rotation) -> &'static [(i32, i32)] {
+        match self {
+            Tetromino::O => &NO_KICKS,
+            _ if to == from.flip() => &HALF_TURN_KICKS[from as usize],
+            Tetromino::I => &I_KICKS[kick_index(from, to)],
+            _ => &JLSTZ_KICKS[kick_index(from, to)],
+        }
+    }
+}
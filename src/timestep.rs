@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use crate::TICKS_PER_SECOND;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+// never try to catch up on more than this much time in one go,
+// after a long stall (dragging the window etc) the rest is just dropped
+const MAX_CATCH_UP: Duration = Duration::from_millis(250);
+
+/// turns real time between rendered frames into a whole number of game ticks,
+/// so the game runs at the same speed whatever the frame rate
+#[derive(Clone, Debug, Default)]
+pub struct FixedTimestep {
+    // elapsed time not yet turned into ticks, kept as nanoseconds * ticks per
+    // second so a tick is exactly one second's worth and nothing drifts
+    accumulator: u64,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add the time since the last call, returns how many ticks to run now
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        let elapsed = elapsed.min(MAX_CATCH_UP).as_nanos() as u64;
+        self.accumulator += elapsed * u64::from(TICKS_PER_SECOND);
+
+        let ticks = self.accumulator / NANOS_PER_SECOND;
+        self.accumulator %= NANOS_PER_SECOND;
+        ticks as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Game, InputFrame, Settings};
+
+    /// feed the timestep frames of the given lengths, count up the ticks
+    fn ticks_for(frames: impl IntoIterator<Item = Duration>) -> u32 {
+        let mut timestep = FixedTimestep::new();
+        frames
+            .into_iter()
+            .map(|frame| timestep.advance(frame))
+            .sum()
+    }
+
+    #[test]
+    fn same_ticks_at_any_frame_rate() {
+        for fps in [30, 60, 75, 144, 240, 1000] {
+            let frame = Duration::from_nanos(NANOS_PER_SECOND / fps);
+            // a little under 10s worth so rounding down each frame can't tip it over
+            let ticks = ticks_for((0..fps * 10).map(|_| frame));
+            assert!(
+                (599..=600).contains(&ticks),
+                "{} fps gave {} ticks in 10s",
+                fps,
+                ticks
+            );
+        }
+    }
+
+    #[test]
+    fn stutters_catch_up() {
+        // mostly smooth with the odd 100ms hitch, still 60 ticks a second
+        let frames = (0..100).map(|i| {
+            if i % 10 == 0 {
+                Duration::from_millis(100)
+            } else {
+                Duration::from_nanos(NANOS_PER_SECOND / 90)
+            }
+        });
+        let total: Duration = frames.clone().sum();
+        let ticks = ticks_for(frames);
+        let expected = (total.as_secs_f64() * f64::from(TICKS_PER_SECOND)) as u32;
+        assert_eq!(ticks, expected);
+    }
+
+    #[test]
+    fn long_stalls_are_dropped() {
+        let mut timestep = FixedTimestep::new();
+        assert_eq!(timestep.advance(Duration::from_secs(5)), 15);
+    }
+
+    /// run a game for `seconds` of synthetic time at a given frame rate,
+    /// holding right the whole time
+    fn play(fps: u64, seconds: u64) -> Game {
+        let mut game = Game::with_seed(Settings::default(), 5);
+        let mut timestep = FixedTimestep::new();
+        let frame = Duration::from_nanos(NANOS_PER_SECOND / fps);
+        let input = InputFrame {
+            right: true,
+            ..InputFrame::default()
+        };
+        for _ in 0..fps * seconds {
+            for _ in 0..timestep.advance(frame) {
+                game.step(input);
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn gravity_and_das_match_across_frame_rates() {
+        // 60fps lines up exactly with the tick so it's the reference
+        let reference = play(60, 20);
+        for fps in [144, 240] {
+            let game = play(fps, 20);
+            assert!(game.grid() == reference.grid(), "{} fps board differs", fps);
+            assert_eq!(game.piece_cells(), reference.piece_cells(), "{} fps", fps);
+        }
+    }
+}